@@ -0,0 +1,217 @@
+//! Inspect and clean up the local cache
+
+use crate::config::Config;
+use crate::discovery::Cache;
+use crate::error::Result;
+use crate::interactive as ui;
+use crate::template::store::ArchiveStore;
+use colored::*;
+use indicatif::HumanBytes;
+use std::time::{Duration, SystemTime};
+
+/// Temp files younger than this may belong to a download still in progress
+const TEMP_FILE_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// Show the cached template list and stored archives with their ages and sizes
+pub fn info(cache_ttl: Option<u64>) -> Result<()> {
+    let cache = open_cache(cache_ttl)?;
+
+    println!("\n{}", "cargo-x402 cache".cyan().bold());
+    println!("{}", "─".repeat(80));
+    println!("{:<16} {}", "Directory", cache.dir().display());
+
+    match cache.read() {
+        Ok(Some(cached)) => {
+            let size = file_size(&cache.cache_file_path());
+            let state = if cached.is_fresh(cache.ttl_hours()) {
+                "fresh".green()
+            } else {
+                "stale".yellow()
+            };
+            println!(
+                "{:<16} {} templates, {}, updated {}h ago ({}, TTL {}h)",
+                "Template list",
+                cached.templates.len(),
+                HumanBytes(size),
+                cached.age_hours(),
+                state,
+                cache.ttl_hours()
+            );
+        }
+        Ok(None) => println!("{:<16} {}", "Template list", "none".dimmed()),
+        Err(e) => println!("{:<16} {} ({})", "Template list", "unreadable".red(), e),
+    }
+
+    let Some(store) = ArchiveStore::new().ok().filter(|s| s.root().is_dir()) else {
+        println!("{:<16} {}", "Archives", "none".dimmed());
+        return Ok(());
+    };
+    let archives = store.archives()?;
+    let total: u64 = archives.iter().map(|a| a.size).sum();
+    println!(
+        "{:<16} {} archives, {}",
+        "Archives",
+        archives.len(),
+        HumanBytes(total)
+    );
+
+    if !archives.is_empty() {
+        println!("{}", "─".repeat(80));
+        println!(
+            "{:<40} {:<10} {:>10} {:>8}",
+            "REPOSITORY", "COMMIT", "SIZE", "AGE"
+        );
+        for archive in &archives {
+            let repo = format!(
+                "{}/{}/{}",
+                archive.key.host, archive.key.owner, archive.key.repo
+            );
+            let commit: String = archive.commit.chars().take(10).collect();
+            let note = if archive.referenced {
+                String::new()
+            } else {
+                "unreferenced".dimmed().to_string()
+            };
+            println!(
+                "{:<40} {:<10} {:>10} {:>8}  {}",
+                repo,
+                commit,
+                HumanBytes(archive.size).to_string(),
+                format_age(archive.modified),
+                note
+            );
+        }
+    }
+    println!("{}", "─".repeat(80));
+
+    Ok(())
+}
+
+/// Delete the cached template list and every stored archive
+pub fn clear(cache_ttl: Option<u64>) -> Result<()> {
+    let cache = open_cache(cache_ttl)?;
+    let list_size = file_size(&cache.cache_file_path());
+    let removed_list = cache.clear()?;
+
+    let (count, archive_size) = match ArchiveStore::new() {
+        Ok(store) => {
+            let archives = store.archives()?;
+            store.clear()?;
+            (archives.len(), archives.iter().map(|a| a.size).sum())
+        }
+        Err(_) => (0, 0),
+    };
+
+    if !removed_list && count == 0 {
+        ui::print_info("Cache is already empty");
+        return Ok(());
+    }
+
+    ui::print_success(&format!(
+        "Removed {}{} archives ({})",
+        if removed_list { "the template list and " } else { "" },
+        count,
+        HumanBytes(list_size + archive_size)
+    ));
+    Ok(())
+}
+
+/// Delete archives no branch or tag points at any more, archives older than
+/// `older_than_days`, and temp files left behind by interrupted downloads
+pub fn prune(older_than_days: Option<u64>) -> Result<()> {
+    let store = ArchiveStore::new()?;
+    let cutoff = older_than_days
+        .map(|days| Duration::from_secs(days.saturating_mul(24 * 60 * 60)))
+        .and_then(|age| SystemTime::now().checked_sub(age));
+
+    let mut count = 0;
+    let mut freed = 0;
+    for archive in store.archives()? {
+        let expired = matches!((cutoff, archive.modified), (Some(c), Some(m)) if m < c);
+        if !archive.referenced || expired {
+            store.remove(&archive)?;
+            count += 1;
+            freed += archive.size;
+        }
+    }
+    freed += remove_stale_temp_files(&store);
+
+    if count == 0 && freed == 0 {
+        ui::print_info("Nothing to prune");
+    } else {
+        ui::print_success(&format!(
+            "Pruned {} archives ({})",
+            count,
+            HumanBytes(freed)
+        ));
+    }
+    Ok(())
+}
+
+fn open_cache(cache_ttl: Option<u64>) -> Result<Cache> {
+    let mut config = Config::load()?;
+    if let Some(ttl) = cache_ttl {
+        config.cache_ttl_hours = Some(ttl);
+    }
+    Cache::from_config(&config)
+}
+
+/// Remove `.tmp*` files that downloads never got to move into place,
+/// returning the bytes freed
+fn remove_stale_temp_files(store: &ArchiveStore) -> u64 {
+    let now = SystemTime::now();
+    let mut freed = 0;
+    for entry in walkdir::WalkDir::new(store.root())
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(".tmp"))
+    {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let old = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > TEMP_FILE_GRACE);
+        if old && std::fs::remove_file(entry.path()).is_ok() {
+            freed += metadata.len();
+        }
+    }
+    freed
+}
+
+fn file_size(path: &std::path::Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Render how long ago something happened, e.g. `5m`, `3h` or `12d`
+fn format_age(time: Option<SystemTime>) -> String {
+    let Some(age) = time.and_then(|t| SystemTime::now().duration_since(t).ok()) else {
+        return "?".to_string();
+    };
+
+    let minutes = age.as_secs() / 60;
+    match minutes {
+        m if m < 60 => format!("{}m", m),
+        m if m < 48 * 60 => format!("{}h", m / 60),
+        m => format!("{}d", m / (24 * 60)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_age() {
+        let ago = |secs| Some(SystemTime::now() - Duration::from_secs(secs));
+
+        assert_eq!(format_age(ago(30)), "0m");
+        assert_eq!(format_age(ago(5 * 60)), "5m");
+        assert_eq!(format_age(ago(3 * 60 * 60)), "3h");
+        assert_eq!(format_age(ago(12 * 24 * 60 * 60)), "12d");
+        assert_eq!(format_age(None), "?");
+    }
+}
@@ -1,64 +1,156 @@
 //! Create a new project from a template
 
-use crate::discovery::{Cache, GitHubDiscovery, TemplateInfo};
+use crate::config::Config;
+use crate::discovery::{Cache, GitHubDiscovery, GitHubHost, TemplateInfo};
 use crate::error::{Error, Result};
 use crate::interactive as ui;
-use crate::schema::Validator;
-use crate::template::{Downloader, Renderer};
+use crate::schema::{Parameter, ParameterValues, Validator};
+use crate::template::diff::{diff_lines, has_changes, DiffLine};
+use crate::template::filter::pattern_matches;
+use crate::template::provenance::PROVENANCE_FILE;
+use crate::template::render::builtin_values;
+use crate::template::source::{ArchiveSource, GitHubSource, GitSource, LocalSource};
+use crate::template::{
+    FileFilter, PlannedFile, Preview, RenderedContent, Renderer, SkipReason, SourceRef,
+    TemplateSource,
+};
 use colored::*;
-use indicatif::ProgressBar;
-use std::collections::HashMap;
-use std::path::Path;
+use indicatif::{HumanBytes, ProgressBar};
+use std::collections::{BTreeMap, HashMap};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+/// Built-in variables that may be overridden with `--define`
+const OVERRIDABLE_BUILTINS: &[&str] = &["author", "date"];
+
+/// Unchanged lines shown around each change by `--diff`
+const DIFF_CONTEXT: usize = 3;
+
+/// Options for the create command
+#[derive(Debug, Default)]
+pub struct CreateOptions {
+    /// Template URL, `owner/repo` shorthand or name
+    pub template: Option<String>,
+
+    /// Project name
+    pub name: Option<String>,
+
+    /// Raw `name=value` parameter definitions
+    pub defines: Vec<String>,
+
+    /// TOML or JSON file with parameter values
+    pub values_file: Option<PathBuf>,
+
+    /// Take every parameter default without prompting
+    pub use_defaults: bool,
+
+    /// Branch, tag or commit SHA to pin the template to
+    pub reference: Option<String>,
+
+    /// Only use cached template listings and archives
+    pub offline: bool,
+
+    /// Hours before the cached template list is refreshed, overriding the config file
+    pub cache_ttl: Option<u64>,
+
+    /// Show the files the template would produce instead of creating the project
+    pub dry_run: bool,
+
+    /// Globs of files whose changes from the template a dry run shows
+    pub diff: Vec<String>,
+}
 
 /// Execute the create command
-pub async fn execute(template_arg: Option<String>, name_arg: Option<String>) -> Result<()> {
+pub async fn execute(options: CreateOptions) -> Result<()> {
+    let interactive = !options.use_defaults && std::io::stdin().is_terminal();
+
+    // Collect supplied parameter values up front so bad input fails fast
+    let mut supplied = match &options.values_file {
+        Some(path) => load_values_file(path)?,
+        None => HashMap::new(),
+    };
+    for define in &options.defines {
+        let (key, value) = parse_define(define)?;
+        supplied.insert(key, value);
+    }
+    let diff_patterns = options
+        .diff
+        .iter()
+        .map(|p| {
+            glob::Pattern::new(p).map_err(|e| {
+                Error::ParameterError(format!("Invalid --diff pattern '{}': {}", p, e))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut config = Config::load()?;
+    if let Some(ttl) = options.cache_ttl {
+        config.cache_ttl_hours = Some(ttl);
+    }
+
+    let offline = options.offline;
+
     // Step 1: Select or resolve template
-    let template = if let Some(template_str) = template_arg {
-        resolve_template(&template_str).await?
-    } else {
+    let selected = if let Some(template_str) = options.template {
+        select_source(&template_str, options.reference, &config, offline, options.dry_run).await?
+    } else if interactive {
         // Interactive mode: fetch and display available templates
-        let templates = fetch_templates().await?;
-        ui::select_template(&templates)?
+        let discovery = GitHubDiscovery::from_config(&config);
+        let templates = fetch_templates(&discovery, &config, offline, !options.dry_run).await?;
+        let template = ui::select_template(&templates)?;
+        let host = discovery.host().clone();
+        SelectedTemplate::github(host, template, options.reference, offline, options.dry_run)
+    } else {
+        return Err(Error::ParameterError(
+            "--template is required when not running interactively".to_string(),
+        ));
     };
 
-    println!(
-        "\n{} Selected template: {} {}",
-        "📦".cyan(),
-        template.name.bold(),
-        format!("({})", template.stars).dimmed()
-    );
+    println!("\n{} Selected template: {}", "📦".cyan(), selected.label);
 
     // Step 2: Get project name
-    let project_name = if let Some(name) = name_arg {
+    let project_name = if let Some(name) = options.name {
         name
+    } else if interactive {
+        ui::prompt_project_name(Some(&selected.default_name))?
     } else {
-        let default_name = template.repo.replace('_', "-").to_lowercase();
-        ui::prompt_project_name(Some(&default_name))?
+        selected.default_name.clone()
     };
 
     // Check if directory already exists
-    if Path::new(&project_name).exists() {
+    let exists = Path::new(&project_name).exists();
+    if exists && !options.dry_run {
         return Err(Error::FileSystemError(format!(
             "Directory '{}' already exists",
             project_name
         )));
     }
 
-    // Step 3: Download template
-    println!("\n{} Downloading template...", "⬇️".cyan());
-    let temp_dir = tempfile::TempDir::new()
+    // Steps 3-4: Fetch the template (local templates are used in place)
+    println!("\n{} Fetching template...", "⬇️".cyan());
+    let scratch = tempfile::TempDir::new()
         .map_err(|e| Error::FileSystemError(format!("Cannot create temp directory: {}", e)))?;
+    let materialized = selected.source.materialize(scratch.path()).await?;
+    let provenance = &materialized.provenance;
+    match (&provenance.reference, &provenance.commit) {
+        (Some(reference), Some(commit)) => println!(
+            "{} Template fetched ({} @ {})",
+            "✅".green(),
+            reference,
+            &commit[..commit.len().min(7)]
+        ),
+        (None, Some(commit)) => println!(
+            "{} Template fetched ({})",
+            "✅".green(),
+            &commit[..commit.len().min(7)]
+        ),
+        _ => println!("{} Template ready", "✅".green()),
+    }
+    let template_dir = materialized.path.as_path();
 
-    let downloader = Downloader::new();
-    downloader
-        .download(&template.url, temp_dir.path())
-        .await?;
-
-    println!("{} Template downloaded", "✅".green());
-
-    // Step 4: Load and validate schema
+    // Step 5: Load and validate schema
     println!("{} Validating template...", "🔍".cyan());
-    let schema_path = temp_dir.path().join("x402.toml");
+    let schema_path = template_dir.join("x402.toml");
 
     if !schema_path.exists() {
         return Err(Error::InvalidSchema(
@@ -69,53 +161,539 @@ pub async fn execute(template_arg: Option<String>, name_arg: Option<String>) ->
     let schema = Validator::load_and_validate(&schema_path)?;
     println!("{} Template validated", "✅".green());
 
-    // Step 5: Prompt for parameters if defined
-    let mut parameters: HashMap<String, String> = HashMap::new();
+    // Step 6: Resolve parameters from flags, defaults or prompts
+    let mut parameters = builtin_values(&project_name);
 
-    // Add default parameters
-    parameters.insert("project_name".to_string(), project_name.clone());
-    parameters.insert("author".to_string(), whoami::realname());
-    parameters.insert("version".to_string(), env!("CARGO_PKG_VERSION").to_string());
-    parameters.insert(
-        "date".to_string(),
-        chrono::Local::now().format("%Y-%m-%d").to_string(),
-    );
+    let empty = HashMap::new();
+    let schema_params = schema.parameters.as_ref().unwrap_or(&empty);
+    let needs_prompt = interactive && schema_params.keys().any(|k| !supplied.contains_key(k));
+    if needs_prompt {
+        println!("\n{} Configure template parameters", "⚙️".cyan());
+    }
+    let custom_params =
+        resolve_parameters(schema_params, supplied, options.use_defaults, interactive)?;
+    parameters.extend(custom_params);
+
+    let mut filter = FileFilter::new(schema.files.as_ref())?;
+    if materialized.working_copy {
+        filter = filter.for_working_copy(template_dir)?;
+    }
 
-    // Prompt for custom parameters
-    if let Some(schema_params) = &schema.parameters {
-        if !schema_params.is_empty() {
-            println!("\n{} Configure template parameters", "⚙️".cyan());
-            let custom_params = ui::prompt_for_parameters(schema_params)?;
-            parameters.extend(custom_params);
+    if options.dry_run {
+        let preview = Renderer::preview(template_dir, &parameters, &filter)?;
+        print_preview(&project_name, &preview);
+        print_diffs(template_dir, &preview, &diff_patterns)?;
+        if exists {
+            ui::print_warning(&format!(
+                "Directory '{}' already exists, so creating the project would fail",
+                project_name
+            ));
         }
+        println!(
+            "\n{} Dry run: nothing was written ({} and git init would follow)",
+            "ℹ️".blue(),
+            PROVENANCE_FILE
+        );
+        return Ok(());
     }
 
-    // Step 6: Render templates
+    // Step 7: Render templates
     println!("{} Rendering template files...", "✨".cyan());
     let spinner = ProgressBar::new_spinner();
     spinner.set_message("Processing files...");
     spinner.enable_steady_tick(std::time::Duration::from_millis(80));
 
-    Renderer::render(temp_dir.path(), Path::new(&project_name), &parameters)?;
+    let report = Renderer::render_with_filter(
+        template_dir,
+        Path::new(&project_name),
+        &parameters,
+        &filter,
+    )?;
 
     spinner.finish_and_clear();
-    println!("{} Template rendered", "✅".green());
+    println!(
+        "{} Template rendered ({} files)",
+        "✅".green(),
+        report.rendered.len()
+    );
+    print_skipped(&report.skipped);
+
+    materialized.provenance.write_to(Path::new(&project_name))?;
 
-    // Step 7: Initialize git repository
+    // Step 8: Initialize git repository
     println!("{} Initializing git repository...", "🔧".cyan());
     initialize_git(&project_name)?;
     println!("{} Git repository initialized", "✅".green());
 
-    // Step 8: Success message
+    // Step 9: Success message
     ui::print_success(&format!("Project created: {}", project_name));
     ui::print_next_steps(&project_name);
 
     Ok(())
 }
 
+/// A template chosen by the user, not yet fetched
+struct SelectedTemplate {
+    /// How the template is shown to the user
+    label: String,
+
+    /// Suggested project name
+    default_name: String,
+
+    /// Where to fetch it from
+    source: Box<dyn TemplateSource>,
+}
+
+impl SelectedTemplate {
+    fn github(
+        host: GitHubHost,
+        template: TemplateInfo,
+        reference: Option<String>,
+        offline: bool,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            label: format!(
+                "{} {}",
+                template.name.bold(),
+                format!("({})", template.stars).dimmed()
+            ),
+            default_name: normalize_project_name(&template.repo),
+            source: Box::new(
+                GitHubSource::new(host, template, reference)
+                    .offline(offline)
+                    .persist(!dry_run),
+            ),
+        }
+    }
+}
+
+/// Pick a template source based on the syntax of the reference
+async fn select_source(
+    template_ref: &str,
+    flag_ref: Option<String>,
+    config: &Config,
+    offline: bool,
+    dry_run: bool,
+) -> Result<SelectedTemplate> {
+    let source_ref = SourceRef::parse(template_ref, config)?;
+    if offline {
+        if let SourceRef::Archive(location) | SourceRef::Git { url: location, .. } = &source_ref
+        {
+            return Err(Error::NetworkError(format!(
+                "{} cannot be fetched in offline mode; only local templates and GitHub \
+                 templates used before are available",
+                location
+            )));
+        }
+    }
+
+    let selected = match source_ref {
+        SourceRef::Local(path) => {
+            reject_ref(&flag_ref, "a local template")?;
+            SelectedTemplate {
+                label: path.display().to_string().bold().to_string(),
+                default_name: name_from_location(&path.to_string_lossy()),
+                source: Box::new(LocalSource::new(path)),
+            }
+        }
+        SourceRef::Archive(url) => {
+            reject_ref(&flag_ref, "an archive URL")?;
+            SelectedTemplate {
+                label: url.bold().to_string(),
+                default_name: name_from_location(&url),
+                source: Box::new(ArchiveSource::new(url)),
+            }
+        }
+        SourceRef::Git { url, reference } => {
+            let reference = merge_refs(flag_ref, reference)?;
+            SelectedTemplate {
+                label: url.bold().to_string(),
+                default_name: name_from_location(&url),
+                source: Box::new(GitSource::new(url, reference)),
+            }
+        }
+        SourceRef::GitHub {
+            host,
+            owner,
+            repo,
+            reference,
+        } => {
+            let reference = merge_refs(flag_ref, reference)?;
+            if offline {
+                let host = GitHubHost::from_config(&host, config);
+                let template = cached_template_info(&host, &owner, &repo, config);
+                SelectedTemplate::github(host, template, reference, true, dry_run)
+            } else {
+                let discovery = GitHubDiscovery::for_host(GitHubHost::resolve(&host, config));
+                let template = discovery.get_template(&owner, &repo).await?;
+                let host = discovery.host().clone();
+                SelectedTemplate::github(host, template, reference, false, dry_run)
+            }
+        }
+        SourceRef::Name(name) => {
+            // Search for it in available templates
+            let discovery = GitHubDiscovery::from_config(config);
+            let templates = fetch_templates(&discovery, config, offline, !dry_run).await?;
+            let template = templates
+                .into_iter()
+                .find(|t| t.repo == name || t.name.to_lowercase() == name.to_lowercase())
+                .ok_or_else(|| Error::TemplateNotFound(name.clone()))?;
+            let host = discovery.host().clone();
+            SelectedTemplate::github(host, template, flag_ref, offline, dry_run)
+        }
+    };
+
+    Ok(selected)
+}
+
+/// Combine `--ref` with an `@ref` suffix, rejecting conflicting values
+pub(crate) fn merge_refs(
+    flag_ref: Option<String>,
+    inline_ref: Option<String>,
+) -> Result<Option<String>> {
+    match (flag_ref, inline_ref) {
+        (Some(flag), Some(inline)) if flag != inline => Err(Error::ValidationError {
+            field: "ref".to_string(),
+            message: format!(
+                "--ref {} conflicts with @{} in the template reference",
+                flag, inline
+            ),
+        }),
+        (flag, inline) => Ok(flag.or(inline)),
+    }
+}
+
+fn reject_ref(flag_ref: &Option<String>, what: &str) -> Result<()> {
+    if flag_ref.is_some() {
+        return Err(Error::ValidationError {
+            field: "ref".to_string(),
+            message: format!("--ref cannot be used with {}", what),
+        });
+    }
+    Ok(())
+}
+
+/// Derive a project name from the last segment of a path or URL
+fn name_from_location(location: &str) -> String {
+    let last = location
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(location);
+    let stem = [".tar.gz", ".tgz", ".zip", ".git"]
+        .iter()
+        .find_map(|ext| last.strip_suffix(ext))
+        .unwrap_or(last);
+
+    if stem.is_empty() {
+        "x402-project".to_string()
+    } else {
+        normalize_project_name(stem)
+    }
+}
+
+pub(crate) fn normalize_project_name(name: &str) -> String {
+    name.replace('_', "-").to_lowercase()
+}
+
+/// Resolve a value for every declared parameter.
+///
+/// Supplied values win and are validated against their declaration. Missing
+/// values fall back to the default when `use_defaults` is set, otherwise they
+/// are prompted for; without a terminal, missing values are an error.
+fn resolve_parameters(
+    schema_params: &HashMap<String, Parameter>,
+    mut supplied: HashMap<String, String>,
+    use_defaults: bool,
+    interactive: bool,
+) -> Result<ParameterValues> {
+    let mut values = ParameterValues::new();
+    let mut missing = Vec::new();
+
+    for (name, param) in schema_params {
+        if let Some(raw) = supplied.remove(name) {
+            let value = param.parse_value(&raw).map_err(|message| Error::ValidationError {
+                field: format!("parameters.{}", name),
+                message,
+            })?;
+            values.insert(name.clone(), value);
+        } else if use_defaults {
+            values.insert(name.clone(), param.default_value());
+        } else if interactive {
+            values.insert(name.clone(), ui::prompt_for_parameter(name, param)?);
+        } else {
+            missing.push(name.as_str());
+        }
+    }
+
+    if !missing.is_empty() {
+        missing.sort_unstable();
+        return Err(Error::ParameterError(format!(
+            "Missing value for {} (pass --define NAME=VALUE, --values-file or --defaults)",
+            missing.join(", ")
+        )));
+    }
+
+    // Whatever is left was not declared by the template
+    for (name, raw) in supplied {
+        if !OVERRIDABLE_BUILTINS.contains(&name.as_str()) {
+            return Err(Error::ParameterError(format!(
+                "Template does not declare a parameter named '{}'",
+                name
+            )));
+        }
+        values.insert(name, raw.into());
+    }
+
+    Ok(values)
+}
+
+/// Parse a `name=value` definition
+pub(crate) fn parse_define(define: &str) -> Result<(String, String)> {
+    match define.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(Error::ParameterError(format!(
+            "Invalid definition '{}', expected NAME=VALUE",
+            define
+        ))),
+    }
+}
+
+/// Load parameter values from a TOML or JSON file (chosen by extension)
+fn load_values_file(path: &Path) -> Result<HashMap<String, String>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        Error::FileSystemError(format!("Cannot read values file '{}': {}", path.display(), e))
+    })?;
+
+    let is_json = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    let mut values = HashMap::new();
+    if is_json {
+        let map: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&content)?;
+        for (key, value) in map {
+            let raw = match value {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Bool(b) => b.to_string(),
+                serde_json::Value::Number(n) => n.to_string(),
+                _ => return Err(values_file_type_error(&key)),
+            };
+            values.insert(key, raw);
+        }
+    } else {
+        let table: toml::Table = toml::from_str(&content)?;
+        for (key, value) in table {
+            let raw = match value {
+                toml::Value::String(s) => s,
+                toml::Value::Boolean(b) => b.to_string(),
+                toml::Value::Integer(i) => i.to_string(),
+                toml::Value::Float(f) => f.to_string(),
+                _ => return Err(values_file_type_error(&key)),
+            };
+            values.insert(key, raw);
+        }
+    }
+
+    Ok(values)
+}
+
+fn values_file_type_error(key: &str) -> Error {
+    Error::ValidationError {
+        field: key.to_string(),
+        message: "Values must be strings, booleans or numbers".to_string(),
+    }
+}
+
+/// List files left out by the template's `[files]` rules
+fn print_skipped(skipped: &[(PathBuf, SkipReason)]) {
+    if skipped.is_empty() {
+        return;
+    }
+
+    ui::print_info(&format!("Skipped {} path(s):", skipped.len()));
+    for (path, reason) in skipped {
+        println!("   {} {}", path.display(), format!("({})", reason).dimmed());
+    }
+}
+
+/// A directory of the dry-run tree
+#[derive(Default)]
+struct TreeNode<'a> {
+    children: BTreeMap<String, TreeNode<'a>>,
+    file: Option<&'a PlannedFile>,
+}
+
+/// Print the files a dry run would write as a tree, with sizes and how each
+/// one is produced
+fn print_preview(project_name: &str, preview: &Preview) {
+    let mut root = TreeNode::default();
+    for file in &preview.files {
+        let mut node = &mut root;
+        for part in file.path.iter() {
+            node = node
+                .children
+                .entry(part.to_string_lossy().into_owned())
+                .or_default();
+        }
+        node.file = Some(file);
+    }
+    let mut rows = Vec::new();
+    tree_rows(&root, "", &mut rows);
+    let width = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0);
+
+    println!("\n{} {}/ {}", "📂".cyan(), project_name.bold(), "(dry run)".dimmed());
+    for (label, file) in rows {
+        let Some(file) = file else {
+            println!("{}", label);
+            continue;
+        };
+        let kind = match &file.content {
+            RenderedContent::Templated(_) => "templated".green(),
+            RenderedContent::Raw { .. } => "raw".yellow(),
+            RenderedContent::Binary { .. } => "binary".blue(),
+            RenderedContent::Link(target) => format!("link -> {}", target.display()).cyan(),
+        };
+        let size = HumanBytes(file.size()).to_string();
+        println!("{:<width$}  {:>10}  {}", label, size, kind, width = width);
+    }
+
+    let count = |f: fn(&RenderedContent) -> bool| {
+        preview.files.iter().filter(|file| f(&file.content)).count()
+    };
+    let total: u64 = preview.files.iter().map(PlannedFile::size).sum();
+    let kinds = [
+        (count(|c| matches!(c, RenderedContent::Templated(_))), "templated"),
+        (count(|c| matches!(c, RenderedContent::Raw { .. })), "raw"),
+        (count(|c| matches!(c, RenderedContent::Binary { .. })), "binary"),
+        (count(|c| matches!(c, RenderedContent::Link(_))), "links"),
+    ];
+    let kinds: Vec<String> = kinds
+        .iter()
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, kind)| format!("{} {}", n, kind))
+        .collect();
+    println!(
+        "\n{} {} files, {}: {}",
+        "📊".cyan(),
+        preview.files.len(),
+        HumanBytes(total),
+        kinds.join(", ")
+    );
+    print_skipped(&preview.skipped);
+}
+
+/// Flatten a tree into labelled rows, directories before their contents
+fn tree_rows<'a>(
+    node: &TreeNode<'a>,
+    indent: &str,
+    rows: &mut Vec<(String, Option<&'a PlannedFile>)>,
+) {
+    let last = node.children.len().saturating_sub(1);
+    for (i, (name, child)) in node.children.iter().enumerate() {
+        let (branch, next) = if i == last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        if child.file.is_some() {
+            rows.push((format!("{}{}{}", indent, branch, name), child.file));
+        } else {
+            rows.push((format!("{}{}{}/", indent, branch, name), None));
+            tree_rows(child, &format!("{}{}", indent, next), rows);
+        }
+    }
+}
+
+/// Show how Liquid changed each previewed file matching `patterns`, by
+/// template or project path
+fn print_diffs(template_dir: &Path, preview: &Preview, patterns: &[glob::Pattern]) -> Result<()> {
+    if patterns.is_empty() {
+        return Ok(());
+    }
+    let selected: Vec<&PlannedFile> = preview
+        .files
+        .iter()
+        .filter(|f| {
+            patterns
+                .iter()
+                .any(|p| pattern_matches(p, &f.path) || pattern_matches(p, &f.source))
+        })
+        .collect();
+    if selected.is_empty() {
+        ui::print_warning("No file matches --diff");
+        return Ok(());
+    }
+
+    for file in selected {
+        println!("\n{} {}", "---".red(), file.source.display());
+        println!("{} {}", "+++".green(), file.path.display());
+        let note = match &file.content {
+            RenderedContent::Templated(text) => {
+                let original = std::fs::read_to_string(template_dir.join(&file.source))
+                    .map_err(|e| Error::FileSystemError(format!("Cannot read file: {}", e)))?;
+                let diff = diff_lines(&original, text, DIFF_CONTEXT);
+                if has_changes(&diff) {
+                    print_diff(&diff);
+                    continue;
+                }
+                "unchanged by Liquid".to_string()
+            }
+            RenderedContent::Raw { size } => {
+                format!("listed in [files] raw; {} copied as is", HumanBytes(*size))
+            }
+            RenderedContent::Binary { size } => {
+                format!("binary; {} copied as is", HumanBytes(*size))
+            }
+            RenderedContent::Link(target) => format!("symlink to {}", target.display()),
+        };
+        println!("{}", format!("({})", note).dimmed());
+    }
+    Ok(())
+}
+
+fn print_diff(diff: &[DiffLine]) {
+    for line in diff {
+        match line {
+            DiffLine::Same(text) => println!(" {}", text),
+            DiffLine::Removed(text) => println!("{}", format!("-{}", text).red()),
+            DiffLine::Added(text) => println!("{}", format!("+{}", text).green()),
+            DiffLine::Elided(count) => {
+                println!("{}", format!("@@ {} unchanged line(s) @@", count).cyan())
+            }
+        }
+    }
+}
+
 /// Fetch templates from GitHub (with caching)
-async fn fetch_templates() -> Result<Vec<TemplateInfo>> {
-    let cache = Cache::new()?;
+///
+/// A fresh listing is saved to the cache only when `persist` is set.
+pub(crate) async fn fetch_templates(
+    discovery: &GitHubDiscovery,
+    config: &Config,
+    offline: bool,
+    persist: bool,
+) -> Result<Vec<TemplateInfo>> {
+    let cache = Cache::from_config(config)?;
+
+    if offline {
+        let cached = cache.read()?.ok_or_else(|| {
+            Error::CacheError(
+                "No cached template list; run 'cargo-x402 list' once while online".to_string(),
+            )
+        })?;
+        if !cached.is_fresh(cache.ttl_hours()) {
+            ui::print_warning(&format!(
+                "Offline: using templates cached {}h ago, which may be out of date",
+                cached.age_hours()
+            ));
+        }
+        return Ok(cached.templates);
+    }
 
     // Try cache first
     if let Some(templates) = cache.load()? {
@@ -130,61 +708,54 @@ async fn fetch_templates() -> Result<Vec<TemplateInfo>> {
 
     // Fetch from GitHub
     ui::print_info("Fetching templates from GitHub...");
-    let discovery = GitHubDiscovery::new();
-
     let spinner = ProgressBar::new_spinner();
     spinner.set_message("Connecting to GitHub...");
     spinner.enable_steady_tick(std::time::Duration::from_millis(80));
 
-    let templates = discovery.discover().await;
+    let cached = discovery.refresh(cache.read().ok().flatten()).await;
     spinner.finish_and_clear();
 
-    let templates = templates?;
+    let cached = cached?;
 
     // Save to cache
-    if let Err(e) = cache.save(&templates) {
-        ui::print_warning(&format!("Could not cache templates: {}", e));
+    if persist {
+        if let Err(e) = cache.store(&cached) {
+            ui::print_warning(&format!("Could not cache templates: {}", e));
+        }
     }
 
-    Ok(templates)
+    Ok(cached.templates)
 }
 
-/// Resolve a template reference (URL, shorthand, or name)
-async fn resolve_template(template_ref: &str) -> Result<TemplateInfo> {
-    // If it looks like a full GitHub URL or shorthand, use it directly
-    if template_ref.starts_with("https://github.com/") || template_ref.contains('/') {
-        // Parse owner/repo from shorthand or URL
-        let (owner, repo) = if template_ref.starts_with("https://") {
-            let parts: Vec<&str> = template_ref.split('/').collect();
-            if parts.len() < 5 {
-                return Err(Error::ValidationError {
-                    field: "template".to_string(),
-                    message: "Invalid GitHub URL".to_string(),
-                });
-            }
-            (parts[3], parts[4])
-        } else {
-            let parts: Vec<&str> = template_ref.split('/').collect();
-            if parts.len() != 2 {
-                return Err(Error::ValidationError {
-                    field: "template".to_string(),
-                    message: "Template reference must be in format 'owner/repo'".to_string(),
-                });
-            }
-            (parts[0], parts[1])
-        };
-
-        // Fetch template info from GitHub
-        let discovery = GitHubDiscovery::new();
-        return discovery.get_template(owner, repo).await;
-    }
-
-    // Otherwise, search for it in available templates
-    let templates = fetch_templates().await?;
-    templates
-        .into_iter()
-        .find(|t| t.repo == template_ref || t.name.to_lowercase() == template_ref.to_lowercase())
-        .ok_or_else(|| Error::TemplateNotFound(template_ref.to_string()))
+/// Template details for `owner/repo` without asking GitHub: from the cached
+/// listing when it is there, otherwise just the names
+fn cached_template_info(
+    host: &GitHubHost,
+    owner: &str,
+    repo: &str,
+    config: &Config,
+) -> TemplateInfo {
+    let cached = Cache::from_config(config)
+        .ok()
+        .and_then(|cache| cache.read().ok().flatten())
+        .and_then(|cached| {
+            cached
+                .templates
+                .into_iter()
+                .find(|t| t.owner.eq_ignore_ascii_case(owner) && t.repo.eq_ignore_ascii_case(repo))
+        });
+
+    cached.unwrap_or_else(|| TemplateInfo {
+        name: repo.to_string(),
+        description: String::new(),
+        url: format!("{}/{}/{}", host.web_url, owner, repo),
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        stars: 0,
+        language: "Unknown".to_string(),
+        topics: Vec::new(),
+        default_branch: None,
+    })
 }
 
 /// Initialize git repository in the new project
@@ -192,7 +763,7 @@ fn initialize_git(project_path: &str) -> Result<()> {
     use std::process::Command;
 
     Command::new("git")
-        .args(&["init", project_path])
+        .args(["init", project_path])
         .output()
         .map_err(|e| {
             Error::FileSystemError(format!("Failed to initialize git repository: {}", e))
@@ -200,13 +771,13 @@ fn initialize_git(project_path: &str) -> Result<()> {
 
     // Add initial files
     Command::new("git")
-        .args(&["-C", project_path, "add", "."])
+        .args(["-C", project_path, "add", "."])
         .output()
         .map_err(|e| Error::FileSystemError(format!("Failed to stage files: {}", e)))?;
 
     // Create initial commit
     Command::new("git")
-        .args(&[
+        .args([
             "-C",
             project_path,
             "commit",
@@ -218,3 +789,118 @@ fn initialize_git(project_path: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::ParameterValue;
+
+    fn sample_parameters() -> HashMap<String, Parameter> {
+        let mut params = HashMap::new();
+        params.insert(
+            "enable_docker".to_string(),
+            Parameter::Boolean {
+                default: true,
+                description: None,
+            },
+        );
+        params.insert(
+            "db_type".to_string(),
+            Parameter::Enum {
+                choices: vec!["postgres".to_string(), "sqlite".to_string()],
+                default: "postgres".to_string(),
+                description: None,
+            },
+        );
+        params
+    }
+
+    #[test]
+    fn test_name_from_location() {
+        assert_eq!(name_from_location("/home/me/My_Template/"), "my-template");
+        assert_eq!(name_from_location("https://mirror.example.com/api-1.0.tar.gz"), "api-1.0");
+        assert_eq!(name_from_location("git@git.example.com:team/payments.git"), "payments");
+        assert_eq!(name_from_location("/"), "x402-project");
+    }
+
+    #[test]
+    fn test_merge_refs() {
+        assert_eq!(merge_refs(None, None).unwrap(), None);
+        assert_eq!(
+            merge_refs(Some("v1".to_string()), None).unwrap(),
+            Some("v1".to_string())
+        );
+        assert_eq!(
+            merge_refs(Some("v1".to_string()), Some("v1".to_string())).unwrap(),
+            Some("v1".to_string())
+        );
+        assert!(merge_refs(Some("v1".to_string()), Some("v2".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_parse_define() {
+        assert_eq!(
+            parse_define("port=8080").unwrap(),
+            ("port".to_string(), "8080".to_string())
+        );
+        assert_eq!(
+            parse_define("url=a=b").unwrap(),
+            ("url".to_string(), "a=b".to_string())
+        );
+        assert!(parse_define("novalue").is_err());
+        assert!(parse_define("=value").is_err());
+    }
+
+    #[test]
+    fn test_resolve_supplied_and_defaults() {
+        let mut supplied = HashMap::new();
+        supplied.insert("enable_docker".to_string(), "no".to_string());
+
+        let values = resolve_parameters(&sample_parameters(), supplied, true, false).unwrap();
+
+        assert_eq!(values["enable_docker"], ParameterValue::Boolean(false));
+        assert_eq!(values["db_type"], ParameterValue::String("postgres".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_missing_without_tty_is_error() {
+        let mut supplied = HashMap::new();
+        supplied.insert("enable_docker".to_string(), "true".to_string());
+
+        let err = resolve_parameters(&sample_parameters(), supplied, false, false).unwrap_err();
+        assert!(err.to_string().contains("db_type"));
+    }
+
+    #[test]
+    fn test_resolve_rejects_invalid_and_unknown_values() {
+        let mut supplied = HashMap::new();
+        supplied.insert("db_type".to_string(), "mysql".to_string());
+        assert!(resolve_parameters(&sample_parameters(), supplied, true, false).is_err());
+
+        let mut supplied = HashMap::new();
+        supplied.insert("typo".to_string(), "x".to_string());
+        assert!(resolve_parameters(&sample_parameters(), supplied, true, false).is_err());
+
+        let mut supplied = HashMap::new();
+        supplied.insert("author".to_string(), "Jane".to_string());
+        let values = resolve_parameters(&sample_parameters(), supplied, true, false).unwrap();
+        assert_eq!(values["author"], ParameterValue::String("Jane".to_string()));
+    }
+
+    #[test]
+    fn test_load_values_file_toml_and_json() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let toml_path = dir.path().join("values.toml");
+        std::fs::write(&toml_path, "enable_docker = false\ndb_type = \"sqlite\"\nport = 3000\n")
+            .unwrap();
+        let values = load_values_file(&toml_path).unwrap();
+        assert_eq!(values["enable_docker"], "false");
+        assert_eq!(values["db_type"], "sqlite");
+        assert_eq!(values["port"], "3000");
+
+        let json_path = dir.path().join("values.json");
+        std::fs::write(&json_path, r#"{"enable_docker": true, "nested": {"a": 1}}"#).unwrap();
+        assert!(load_values_file(&json_path).is_err());
+    }
+}
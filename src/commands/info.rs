@@ -0,0 +1,232 @@
+//! Show a template's manifest without creating a project
+
+use super::create::{fetch_templates, merge_refs};
+use crate::config::Config;
+use crate::discovery::{GitHubDiscovery, GitHubHost};
+use crate::error::{Error, Result};
+use crate::schema::template::FileRules;
+use crate::schema::{Parameter, TemplateMetadata, TemplateSchema, Validator};
+use crate::template::filter::DEFAULT_EXCLUDES;
+use crate::template::SourceRef;
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+const MANIFEST_FILE: &str = "x402.toml";
+
+/// Options for the info command
+#[derive(Debug, Default)]
+pub struct InfoOptions {
+    /// `owner/repo[@ref]`, GitHub URL, template name or local directory
+    pub template: String,
+
+    /// Branch, tag or commit SHA to read the manifest at
+    pub reference: Option<String>,
+
+    /// Print machine-readable JSON instead of text
+    pub json: bool,
+
+    /// Only use cached template listings
+    pub offline: bool,
+
+    /// Hours before the cached template list is refreshed, overriding the config file
+    pub cache_ttl: Option<u64>,
+}
+
+/// Everything `info` reports about a template
+#[derive(Debug, Serialize)]
+struct TemplateDetails<'a> {
+    /// Where the manifest was read from
+    source: &'a str,
+    /// Branch, tag or commit it was read at, if pinned
+    reference: Option<&'a str>,
+    /// Whether the manifest passed validation
+    valid: bool,
+    /// Why validation failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    template: &'a TemplateMetadata,
+    /// Parameters sorted by name
+    parameters: BTreeMap<&'a str, &'a Parameter>,
+    files: Option<&'a FileRules>,
+}
+
+/// Execute the info command
+pub async fn execute(options: InfoOptions) -> Result<()> {
+    let mut config = Config::load()?;
+    if let Some(ttl) = options.cache_ttl {
+        config.cache_ttl_hours = Some(ttl);
+    }
+
+    let (source, reference, content) = fetch_manifest(&options, &config).await?;
+    let schema = Validator::parse(&content)?;
+    let validation = Validator::validate_schema(&schema);
+
+    let details = TemplateDetails {
+        source: &source,
+        reference: reference.as_deref(),
+        valid: validation.is_ok(),
+        error: validation.as_ref().err().map(|e| e.to_string()),
+        template: &schema.template,
+        parameters: schema
+            .parameters
+            .iter()
+            .flatten()
+            .map(|(name, param)| (name.as_str(), param))
+            .collect(),
+        files: schema.files.as_ref(),
+    };
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&details)?);
+    } else {
+        print_details(&details, &schema);
+    }
+
+    validation
+}
+
+/// Find the template and read its x402.toml, returning a label for where it
+/// came from, the ref it was read at and the file's contents
+async fn fetch_manifest(
+    options: &InfoOptions,
+    config: &Config,
+) -> Result<(String, Option<String>, String)> {
+    let flag_ref = options.reference.clone();
+
+    let (host, owner, repo, reference) = match SourceRef::parse(&options.template, config)? {
+        SourceRef::Local(path) => {
+            if flag_ref.is_some() {
+                return Err(Error::ValidationError {
+                    field: "ref".to_string(),
+                    message: "--ref cannot be used with a local template".to_string(),
+                });
+            }
+            let manifest = path.join(MANIFEST_FILE);
+            let content = std::fs::read_to_string(&manifest).map_err(|e| {
+                Error::FileSystemError(format!("Cannot read {}: {}", manifest.display(), e))
+            })?;
+            return Ok((path.display().to_string(), None, content));
+        }
+        SourceRef::Archive(location) | SourceRef::Git { url: location, .. } => {
+            return Err(Error::ParameterError(format!(
+                "info works with GitHub repositories and local directories, not {}",
+                location
+            )));
+        }
+        SourceRef::GitHub {
+            host,
+            owner,
+            repo,
+            reference,
+        } => (host, owner, repo, merge_refs(flag_ref, reference)?),
+        SourceRef::Name(name) => {
+            let discovery = GitHubDiscovery::from_config(config);
+            let templates = fetch_templates(&discovery, config, options.offline, true).await?;
+            let template = templates
+                .into_iter()
+                .find(|t| t.repo == name || t.name.to_lowercase() == name.to_lowercase())
+                .ok_or_else(|| Error::TemplateNotFound(name.clone()))?;
+            let host = discovery.host().name.clone();
+            (host, template.owner, template.repo, flag_ref)
+        }
+    };
+
+    if options.offline {
+        return Err(Error::NetworkError(format!(
+            "Cannot read the manifest of {}/{} in offline mode",
+            owner, repo
+        )));
+    }
+
+    let host = GitHubHost::resolve(&host, config);
+    let source = format!("{}/{}/{}", host.web_url, owner, repo);
+    let content = GitHubDiscovery::for_host(host)
+        .get_file(&owner, &repo, MANIFEST_FILE, reference.as_deref())
+        .await?;
+    Ok((source, reference, content))
+}
+
+fn print_details(details: &TemplateDetails, schema: &TemplateSchema) {
+    let meta = details.template;
+
+    println!("\n{} {}", meta.name.cyan().bold(), meta.version.dimmed());
+    println!("{}", meta.description);
+    println!("{}", "─".repeat(80));
+
+    let source = match details.reference {
+        Some(reference) => format!("{}@{}", details.source, reference),
+        None => details.source.to_string(),
+    };
+    println!("{:<14} {}", "Source", source);
+    println!("{:<14} {}", "Repository", meta.repository);
+    println!("{:<14} {}", "Authors", meta.authors.join(", "));
+    if !meta.tags.is_empty() {
+        println!("{:<14} {}", "Tags", meta.tags.join(", "));
+    }
+    if let Some(version) = &meta.min_rust_version {
+        println!("{:<14} {}", "Min Rust", version);
+    }
+    if let Some(version) = &meta.min_x402_cli_version {
+        println!("{:<14} {}", "Min cargo-x402", version);
+    }
+
+    println!("\n{}", "Parameters".bold());
+    if details.parameters.is_empty() {
+        println!("  {}", "none".dimmed());
+    }
+    for (name, param) in &details.parameters {
+        let (kind, description) = match param {
+            Parameter::String { description, .. } => ("string", description),
+            Parameter::Boolean { description, .. } => ("boolean", description),
+            Parameter::Enum { description, .. } => ("enum", description),
+        };
+        println!(
+            "  {:<24} {:<8} default: {}",
+            name.cyan(),
+            kind,
+            param.default_value()
+        );
+        match param {
+            Parameter::String {
+                pattern: Some(pattern),
+                ..
+            } => println!("  {:<24} {:<8} pattern: {}", "", "", pattern),
+            Parameter::Enum { choices, .. } => {
+                println!("  {:<24} {:<8} choices: {}", "", "", choices.join(", "))
+            }
+            _ => {}
+        }
+        if let Some(description) = description {
+            println!("  {:<24} {:<8} {}", "", "", description.dimmed());
+        }
+    }
+
+    println!("\n{}", "Files".bold());
+    let files = schema.files.clone().unwrap_or_default();
+    if files.include.is_empty() {
+        println!("  {:<10} {}", "include", "all files".dimmed());
+    } else {
+        println!("  {:<10} {}", "include", files.include.join(", "));
+    }
+    if files.exclude.is_empty() {
+        // What the renderer falls back to, so this matches what create writes
+        println!(
+            "  {:<10} {} {}",
+            "exclude",
+            DEFAULT_EXCLUDES.join(", "),
+            "(default)".dimmed()
+        );
+    } else {
+        println!("  {:<10} {}", "exclude", files.exclude.join(", "));
+    }
+    if !files.raw.is_empty() {
+        println!("  {:<10} {}", "raw", files.raw.join(", "));
+    }
+
+    println!("{}", "─".repeat(80));
+    match &details.error {
+        None => println!("{} Manifest is valid", "✅".green()),
+        Some(_) => println!("{} Manifest has problems (see below)", "⚠️".yellow()),
+    }
+}
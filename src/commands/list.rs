@@ -1,35 +1,72 @@
 //! List available templates
 
+use crate::config::Config;
+use crate::discovery::cache::CachedTemplates;
 use crate::discovery::{Cache, GitHubDiscovery};
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::interactive as ui;
 use colored::*;
 use indicatif::ProgressBar;
 
+/// Options for the list command
+#[derive(Debug, Default)]
+pub struct ListOptions {
+    /// Bypass the cache TTL and ask GitHub again
+    pub refresh: bool,
+
+    /// Only show templates matching these tags
+    pub tags: Option<Vec<String>>,
+
+    /// Only use the cached template list
+    pub offline: bool,
+
+    /// Hours before the cached template list is refreshed, overriding the config file
+    pub cache_ttl: Option<u64>,
+}
+
 /// Execute the list command
-pub async fn execute(refresh: bool, tags: Option<Vec<String>>) -> Result<()> {
+pub async fn execute(options: ListOptions) -> Result<()> {
+    let ListOptions {
+        refresh,
+        tags,
+        offline,
+        cache_ttl,
+    } = options;
+
+    let mut config = Config::load()?;
+    if let Some(ttl) = cache_ttl {
+        config.cache_ttl_hours = Some(ttl);
+    }
+
     // Initialize cache
-    let cache = Cache::new()?;
+    let cache = Cache::from_config(&config)?;
 
     // Load templates from cache or GitHub
-    let templates = if refresh {
+    let listing = if offline {
+        if refresh {
+            ui::print_warning("Ignoring --refresh in offline mode");
+        }
+        load_offline(&cache)?
+    } else if refresh {
         ui::print_info("Refreshing template list...");
-        load_from_github(&cache).await?
+        load_from_github(&cache, &config).await?
     } else {
         // Try cache first
-        match cache.load()? {
-            Some(templates) => {
-                if let Ok(Some(age)) = cache.age_hours() {
-                    ui::print_info(&format!("Using cached templates ({}h old, use --refresh to update)", age));
-                }
-                templates
+        match cache.load_entry()? {
+            Some(cached) => {
+                ui::print_info(&format!("Using cached templates ({}h old, use --refresh to update)", cached.age_hours()));
+                cached
             }
             None => {
                 ui::print_info("Loading templates from GitHub...");
-                load_from_github(&cache).await?
+                load_from_github(&cache, &config).await?
             }
         }
     };
+    let truncation = listing
+        .truncated
+        .then_some((listing.templates.len(), listing.total_count));
+    let templates = listing.templates;
 
     if templates.is_empty() {
         ui::print_warning("No templates found");
@@ -81,6 +118,12 @@ pub async fn execute(refresh: bool, tags: Option<Vec<String>>) -> Result<()> {
     }
 
     println!("{}", "─".repeat(100));
+    if let Some((fetched, total_count)) = truncation {
+        ui::print_warning(&format!(
+            "Showing the {} most starred of {} templates (raise max_templates in the config file to see more)",
+            fetched, total_count
+        ));
+    }
     println!(
         "\nTip: Use {} to create a project from a template",
         "cargo-x402 create".bold()
@@ -93,22 +136,58 @@ pub async fn execute(refresh: bool, tags: Option<Vec<String>>) -> Result<()> {
     Ok(())
 }
 
+/// Load the cached templates whatever their age, without touching the network
+fn load_offline(cache: &Cache) -> Result<CachedTemplates> {
+    let cached = cache.read()?.ok_or_else(|| {
+        Error::CacheError(
+            "No cached template list; run 'cargo-x402 list' once while online".to_string(),
+        )
+    })?;
+
+    if cached.is_fresh(cache.ttl_hours()) {
+        ui::print_info(&format!("Using cached templates ({}h old)", cached.age_hours()));
+    } else {
+        ui::print_warning(&format!(
+            "Offline: showing cached templates from {}h ago, which may be out of date",
+            cached.age_hours()
+        ));
+    }
+    Ok(cached)
+}
+
 /// Load templates from GitHub and cache them
-async fn load_from_github(cache: &Cache) -> Result<Vec<crate::discovery::TemplateInfo>> {
-    let discovery = GitHubDiscovery::new();
+async fn load_from_github(cache: &Cache, config: &Config) -> Result<CachedTemplates> {
+    let discovery = GitHubDiscovery::from_config(config);
     let spinner = ProgressBar::new_spinner();
     spinner.set_message("Fetching templates from GitHub...");
     spinner.enable_steady_tick(std::time::Duration::from_millis(80));
 
-    let result = discovery.discover().await;
+    // Cached entries of any age carry validators for a conditional request
+    let previous = cache.read().ok().flatten();
+    let result = discovery.refresh(previous.clone()).await;
     spinner.finish_and_clear();
 
-    let templates = result?;
+    let cached = match result {
+        Ok(cached) => cached,
+        // Throttled: an outdated list beats no list
+        Err(e @ Error::RateLimited { .. }) => match previous {
+            Some(stale) => {
+                ui::print_warning(&format!(
+                    "{}\nShowing cached templates from {}h ago instead",
+                    e.to_string().lines().next().unwrap_or_default(),
+                    stale.age_hours()
+                ));
+                return Ok(stale);
+            }
+            None => return Err(e),
+        },
+        Err(e) => return Err(e),
+    };
 
     // Save to cache
-    if let Err(e) = cache.save(&templates) {
+    if let Err(e) = cache.store(&cached) {
         ui::print_warning(&format!("Failed to cache templates: {}", e));
     }
 
-    Ok(templates)
+    Ok(cached)
 }
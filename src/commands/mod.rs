@@ -15,10 +15,46 @@
 //! Create a new project from a template by downloading, validating, and rendering it.
 //! Supports interactive prompts or non-interactive specification via flags.
 //!
+//! ### info
+//!
+//! Show a template's metadata, parameters and file rules, read from its
+//! x402.toml through the GitHub contents API.
+//!
+//! ### validate
+//!
+//! Lint a local template: manifest checks, Liquid syntax, undeclared and
+//! unused variables, and `[files]` patterns that match nothing.
+//!
+//! ### test
+//!
+//! Render a local template with each combination of its boolean and enum
+//! parameters and run a check command (`cargo check --offline` by default)
+//! in every result.
+//!
+//! ### template init
+//!
+//! Scaffold a new template: build an x402.toml from prompts (or an example
+//! with `--defaults`), plus sample Liquid files and README usage docs.
+//!
+//! ### template extract
+//!
+//! Copy a working project, replacing chosen literals (crate name, author,
+//! port, payTo address) with Liquid variables, and check that rendering with
+//! the defaults reproduces it.
+//!
+//! ### cache
+//!
+//! Show, prune or clear the cached template list and downloaded archives.
+//!
 //! ## Submodules
 //!
 //! - [`list`]: Template discovery and filtering
 //! - [`create`]: Project creation from templates
+//! - [`info`]: Template manifest display
+//! - [`validate`]: Template linting
+//! - [`test`]: Parameter matrix builds
+//! - [`template`]: Template authoring tools
+//! - [`cache`]: Cache inspection and cleanup
 //!
 //! ## Example
 //!
@@ -30,5 +66,10 @@
 //! // The CLI provides the primary interface for these operations
 //! ```
 
+pub mod cache;
 pub mod create;
+pub mod info;
 pub mod list;
+pub mod template;
+pub mod test;
+pub mod validate;
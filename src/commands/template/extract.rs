@@ -0,0 +1,366 @@
+//! Turn an existing project into a template
+
+use super::init::{check_parameter_name, default_author, split_list, write_file};
+use crate::commands::create::parse_define;
+use crate::error::{Error, Result};
+use crate::interactive as ui;
+use crate::schema::{Parameter, TemplateMetadata, TemplateSchema, Validator};
+use crate::template::extract::{Candidate, Extractor, Replacement};
+use crate::template::render::builtin_values;
+use colored::*;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "x402.toml";
+
+/// Built-in variables a literal may be mapped to instead of a new parameter
+const EXTRACTABLE_BUILTINS: &[&str] = &["project_name", "author"];
+
+/// Options for `template extract`
+#[derive(Debug, Default)]
+pub struct ExtractOptions {
+    /// Project to turn into a template
+    pub project: PathBuf,
+
+    /// Where to write the template (default: `<project>-template` next to it)
+    pub output: Option<PathBuf>,
+
+    /// Raw `name=literal` values to replace, on top of the detected ones
+    pub vars: Vec<String>,
+
+    /// Replace every detected value without prompting
+    pub use_defaults: bool,
+
+    /// Write into an existing, non-empty output directory
+    pub force: bool,
+}
+
+/// A literal chosen to become a variable
+struct Variable {
+    name: String,
+    literal: String,
+    description: Option<String>,
+    pattern: Option<String>,
+}
+
+/// Execute `template extract`
+pub fn execute(options: ExtractOptions) -> Result<()> {
+    let project = options.project.canonicalize().map_err(|e| {
+        Error::FileSystemError(format!("Cannot open {}: {}", options.project.display(), e))
+    })?;
+    if !project.is_dir() {
+        return Err(Error::FileSystemError(format!(
+            "{} is not a directory",
+            project.display()
+        )));
+    }
+    let dir_name = project
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "project".to_string());
+
+    let output = match options.output {
+        Some(path) => path,
+        None => project
+            .parent()
+            .unwrap_or(&project)
+            .join(format!("{}-template", dir_name)),
+    };
+    check_output(&project, &output, options.force)?;
+
+    let interactive = !options.use_defaults && std::io::stdin().is_terminal();
+    let detected = Extractor::detect(&project)?;
+    let mut variables = if interactive {
+        choose_variables(detected)?
+    } else {
+        detected.into_iter().map(Variable::from).collect()
+    };
+    for var in &options.vars {
+        let (name, literal) = parse_define(var)?;
+        variables.retain(|v| v.name != name);
+        variables.push(Variable {
+            name,
+            literal,
+            description: None,
+            pattern: None,
+        });
+    }
+    check_variables(&variables)?;
+
+    println!("\n{} Extracting template...", "✨".cyan());
+    let replacements: Vec<Replacement> = variables
+        .iter()
+        .flat_map(|v| Replacement::for_variable(&v.name, &v.literal))
+        .collect();
+    let report = Extractor::extract(&project, &output, &replacements)?;
+
+    // Only declare what was actually found
+    let mut used = Vec::new();
+    for variable in variables {
+        let count: usize = Replacement::for_variable(&variable.name, &variable.literal)
+            .iter()
+            .filter_map(|r| report.replaced.get(&r.literal))
+            .sum();
+        if count == 0 {
+            ui::print_warning(&format!(
+                "'{}' does not appear in the project; not adding {}",
+                variable.literal, variable.name
+            ));
+        } else {
+            println!(
+                "  {:<16} {:<44} {} replaced",
+                variable.name.cyan(),
+                variable.literal,
+                count
+            );
+            used.push(variable);
+        }
+    }
+    println!(
+        "{} Copied {} files, {} templated",
+        "✅".green(),
+        report.files.len(),
+        report.templated.len()
+    );
+
+    let schema = build_schema(&project, &dir_name, &used)?;
+    let manifest = output.join(MANIFEST_FILE);
+    let toml = schema
+        .to_toml()
+        .map_err(|e| Error::TomlError(format!("Cannot serialize x402.toml: {}", e)))?;
+    write_file(&manifest, &toml)?;
+    Validator::load_and_validate(&manifest)?;
+    println!("{} Wrote {}", "✅".green(), MANIFEST_FILE);
+
+    // Rendering with the original values must give back the original project
+    println!("{} Checking a render with the defaults...", "🔍".cyan());
+    let mut parameters = builtin_values(&dir_name);
+    for variable in &used {
+        parameters.insert(variable.name.clone(), variable.literal.clone().into());
+    }
+    let mismatched = Extractor::verify(&project, &output, &parameters)?;
+    if !mismatched.is_empty() {
+        let files: Vec<String> = mismatched.iter().map(|p| p.display().to_string()).collect();
+        return Err(Error::ValidationError {
+            field: output.display().to_string(),
+            message: format!(
+                "rendering with the defaults does not reproduce the project: {}",
+                files.join(", ")
+            ),
+        });
+    }
+    println!(
+        "{} Rendering with the defaults reproduces the project",
+        "✅".green()
+    );
+
+    ui::print_success(&format!("Template written to {}", output.display()));
+    println!("\n{}", "Next steps:".cyan().bold());
+    println!("  Review {} and the templated files", MANIFEST_FILE);
+    println!("  {} cargo-x402 validate {}", "$".dimmed(), output.display());
+    println!("  {} cargo-x402 test {}", "$".dimmed(), output.display());
+    Ok(())
+}
+
+impl From<Candidate> for Variable {
+    fn from(candidate: Candidate) -> Self {
+        Self {
+            name: candidate.variable,
+            literal: candidate.literal,
+            description: Some(capitalize(candidate.label)),
+            pattern: candidate.pattern.map(String::from),
+        }
+    }
+}
+
+/// The output must not be inside the project (it would be copied into
+/// itself) or hold anything already, unless `force`
+fn check_output(project: &Path, output: &Path, force: bool) -> Result<()> {
+    let absolute = std::env::current_dir()
+        .map(|cwd| cwd.join(output))
+        .unwrap_or_else(|_| output.to_path_buf());
+    let resolved = absolute
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .map(|parent| parent.join(absolute.file_name().unwrap_or_default()))
+        .unwrap_or(absolute);
+    if resolved.starts_with(project) {
+        return Err(Error::FileSystemError(format!(
+            "The template cannot be written inside the project ({}); choose another --output",
+            output.display()
+        )));
+    }
+
+    let occupied = std::fs::read_dir(output)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if occupied && !force {
+        return Err(Error::FileSystemError(format!(
+            "{} is not empty; use --force to write into it",
+            output.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Ask which detected values to replace, and for any others
+fn choose_variables(detected: Vec<Candidate>) -> Result<Vec<Variable>> {
+    let mut variables = Vec::new();
+    for candidate in detected {
+        let question = format!(
+            "Replace {} '{}' with {{{{ {} }}}}?",
+            candidate.label, candidate.literal, candidate.variable
+        );
+        if ui::prompt_confirm(&question, true)? {
+            variables.push(Variable::from(candidate));
+        }
+    }
+
+    while ui::prompt_confirm("Replace another value?", false)? {
+        let declared: HashMap<String, Parameter> = HashMap::new();
+        let literal = ui::prompt_text("Value as it appears in the project", None, |v| {
+            if v.is_empty() {
+                Err("Enter the text to replace".to_string())
+            } else {
+                Ok(())
+            }
+        })?;
+        let name = ui::prompt_text("Variable name", None, |v| {
+            if EXTRACTABLE_BUILTINS.contains(&v.as_str()) {
+                Ok(())
+            } else {
+                check_parameter_name(v, &declared)
+            }
+        })?;
+        let description = ui::prompt_text("Description (optional)", None, |_| Ok(()))?;
+        variables.retain(|v| v.name != name);
+        variables.push(Variable {
+            name,
+            literal,
+            description: Some(description).filter(|d| !d.is_empty()),
+            pattern: None,
+        });
+    }
+    Ok(variables)
+}
+
+fn check_variables(variables: &[Variable]) -> Result<()> {
+    let none = HashMap::new();
+    let mut literals = HashMap::new();
+    for variable in variables {
+        if !EXTRACTABLE_BUILTINS.contains(&variable.name.as_str()) {
+            check_parameter_name(&variable.name, &none).map_err(|message| {
+                Error::ParameterError(format!("Invalid variable '{}': {}", variable.name, message))
+            })?;
+        }
+        if variable.literal.is_empty() {
+            return Err(Error::ParameterError(format!(
+                "No value to replace with {}",
+                variable.name
+            )));
+        }
+        if let Some(other) = literals.insert(&variable.literal, &variable.name) {
+            return Err(Error::ParameterError(format!(
+                "'{}' cannot become both {} and {}",
+                variable.literal, other, variable.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Manifest for the extracted template: metadata from Cargo.toml where it
+/// has it, and one string parameter per replaced value
+fn build_schema(project: &Path, dir_name: &str, variables: &[Variable]) -> Result<TemplateSchema> {
+    let package = std::fs::read_to_string(project.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| toml::from_str::<toml::Value>(&content).ok())
+        .and_then(|manifest| manifest.get("package").cloned());
+    let field = |key: &str| {
+        package
+            .as_ref()
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    };
+
+    let name: String = field("name")
+        .unwrap_or_else(|| dir_name.to_string())
+        .chars()
+        .take(100)
+        .collect();
+    let description = field("description")
+        .filter(|d| (10..=200).contains(&d.len()))
+        .unwrap_or_else(|| format!("An x402 template extracted from {}", name));
+    let repository = field("repository")
+        .filter(|r| r.starts_with("https://github.com/"))
+        .map(|r| format!("{}-template", r.trim_end_matches('/').trim_end_matches(".git")))
+        .unwrap_or_else(|| format!("https://github.com/{}/{}-template", whoami::username(), name));
+    let author = variables
+        .iter()
+        .find(|v| v.name == "author")
+        .map(|v| v.literal.clone())
+        .unwrap_or_else(default_author);
+
+    let parameters: HashMap<String, Parameter> = variables
+        .iter()
+        .filter(|v| !EXTRACTABLE_BUILTINS.contains(&v.name.as_str()))
+        .map(|v| {
+            let param = Parameter::String {
+                default: v.literal.clone(),
+                pattern: v.pattern.clone(),
+                description: v.description.clone(),
+            };
+            (v.name.clone(), param)
+        })
+        .collect();
+
+    let schema = TemplateSchema {
+        template: TemplateMetadata {
+            name,
+            description,
+            version: "0.1.0".to_string(),
+            authors: split_list(&author),
+            repository,
+            tags: vec!["x402".to_string()],
+            min_rust_version: None,
+            min_x402_cli_version: None,
+        },
+        parameters: Some(parameters).filter(|p| !p.is_empty()),
+        files: None,
+    };
+    Validator::validate_schema(&schema)?;
+    Ok(schema)
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str, literal: &str) -> Variable {
+        Variable {
+            name: name.to_string(),
+            literal: literal.to_string(),
+            description: None,
+            pattern: None,
+        }
+    }
+
+    #[test]
+    fn test_check_variables() {
+        assert!(check_variables(&[variable("project_name", "app"), variable("port", "80")]).is_ok());
+        assert!(check_variables(&[variable("Port", "80")]).is_err());
+        assert!(check_variables(&[variable("date", "2024-01-01")]).is_err());
+        assert!(check_variables(&[variable("port", "")]).is_err());
+        assert!(check_variables(&[variable("a", "80"), variable("b", "80")]).is_err());
+    }
+}
@@ -0,0 +1,509 @@
+//! Scaffold a new template: x402.toml, a sample Liquid file and README docs
+
+use crate::commands::create::normalize_project_name;
+use crate::error::{Error, Result};
+use crate::interactive as ui;
+use crate::schema::template::FileRules;
+use crate::schema::{Parameter, TemplateMetadata, TemplateSchema, Validator};
+use crate::template::render::BUILTIN_VARIABLES;
+use colored::*;
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE: &str = "x402.toml";
+
+/// Heading of the section added to the template's README
+const README_HEADING: &str = "## Using this template";
+
+/// Options for `template init`
+#[derive(Debug, Default)]
+pub struct InitOptions {
+    /// Template directory, created if missing
+    pub path: PathBuf,
+
+    /// Write an example manifest without prompting
+    pub use_defaults: bool,
+
+    /// Overwrite an existing x402.toml
+    pub force: bool,
+}
+
+/// Execute `template init`
+pub fn execute(options: InitOptions) -> Result<()> {
+    let path = options.path.as_path();
+    let manifest = path.join(MANIFEST_FILE);
+    if manifest.exists() && !options.force {
+        return Err(Error::FileSystemError(format!(
+            "{} already exists; use --force to overwrite it",
+            manifest.display()
+        )));
+    }
+    std::fs::create_dir_all(path).map_err(|e| {
+        Error::FileSystemError(format!("Cannot create {}: {}", path.display(), e))
+    })?;
+
+    let dir_name = path
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "my-template".to_string());
+
+    let interactive = !options.use_defaults && std::io::stdin().is_terminal();
+    let schema = if interactive {
+        println!("\n{} Describe your template", "📝".cyan());
+        prompt_schema(&dir_name)?
+    } else {
+        example_schema(&dir_name)
+    };
+    Validator::validate_schema(&schema)?;
+
+    let toml = schema
+        .to_toml()
+        .map_err(|e| Error::TomlError(format!("Cannot serialize x402.toml: {}", e)))?;
+    write_file(&manifest, &toml)?;
+    println!("{} Wrote {}", "✅".green(), MANIFEST_FILE);
+
+    for (file, content) in sample_files(&schema) {
+        let target = path.join(file);
+        if target.exists() {
+            println!("{} Kept existing {}", "ℹ️".blue(), file);
+        } else {
+            write_file(&target, &content)?;
+            println!("{} Wrote sample {}", "✅".green(), file);
+        }
+    }
+
+    if add_readme_section(&path.join("README.md"), &schema)? {
+        println!("{} Added \"{}\" to README.md", "✅".green(), &README_HEADING[3..]);
+    }
+
+    // What we wrote must load the same way `create` will load it
+    Validator::load_and_validate(&manifest)?;
+
+    ui::print_success(&format!("Template initialized in {}", path.display()));
+    println!("\n{}", "Next steps:".cyan().bold());
+    println!("  Add your files, using parameters as {{{{ name }}}}");
+    println!("  {} cargo-x402 validate {}", "$".dimmed(), path.display());
+    println!("  {} cargo-x402 test {}", "$".dimmed(), path.display());
+    Ok(())
+}
+
+/// The manifest `--defaults` writes: metadata derived from the directory
+/// name and one example parameter of each type
+fn example_schema(dir_name: &str) -> TemplateSchema {
+    let slug = normalize_project_name(dir_name);
+    let name: String = dir_name.chars().take(100).collect();
+
+    let mut parameters = HashMap::new();
+    parameters.insert(
+        "port".to_string(),
+        Parameter::String {
+            default: "3000".to_string(),
+            pattern: Some("^[0-9]+$".to_string()),
+            description: Some("Port the server listens on".to_string()),
+        },
+    );
+    parameters.insert(
+        "enable_docker".to_string(),
+        Parameter::Boolean {
+            default: true,
+            description: Some("Include Docker support".to_string()),
+        },
+    );
+    parameters.insert(
+        "network".to_string(),
+        Parameter::Enum {
+            choices: vec!["base-sepolia".to_string(), "base".to_string()],
+            default: "base-sepolia".to_string(),
+            description: Some("Network payments settle on".to_string()),
+        },
+    );
+
+    TemplateSchema {
+        template: TemplateMetadata {
+            description: format!("An x402 template for {}", name),
+            name,
+            version: "0.1.0".to_string(),
+            authors: vec![default_author()],
+            repository: format!("https://github.com/{}/{}", whoami::username(), slug),
+            tags: vec!["x402".to_string()],
+            min_rust_version: None,
+            min_x402_cli_version: None,
+        },
+        parameters: Some(parameters),
+        files: None,
+    }
+}
+
+/// Build a manifest from answers to prompts
+fn prompt_schema(dir_name: &str) -> Result<TemplateSchema> {
+    let defaults = example_schema(dir_name).template;
+
+    let name = ui::prompt_text("Template name", Some(&defaults.name), |v| {
+        match v.trim().len() {
+            0 => Err("Name is required".to_string()),
+            1..=100 => Ok(()),
+            _ => Err("Name must be 100 characters or less".to_string()),
+        }
+    })?;
+    let description = ui::prompt_text("Description", Some(&defaults.description), |v| {
+        match v.trim().len() {
+            10..=200 => Ok(()),
+            _ => Err("Description must be 10-200 characters".to_string()),
+        }
+    })?;
+    let version = ui::prompt_text("Version", Some(&defaults.version), |v| {
+        semver::Version::parse(v)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid semantic version: {}", e))
+    })?;
+    let authors = ui::prompt_text(
+        "Authors (comma-separated)",
+        Some(&defaults.authors.join(", ")),
+        |v| {
+            if split_list(v).is_empty() {
+                Err("At least one author is required".to_string())
+            } else {
+                Ok(())
+            }
+        },
+    )?;
+    let repository = ui::prompt_text("Repository URL", Some(&defaults.repository), |v| {
+        if v.starts_with("https://github.com/") {
+            Ok(())
+        } else {
+            Err("Must be an HTTPS GitHub URL (https://github.com/...)".to_string())
+        }
+    })?;
+    let tags = ui::prompt_text("Tags (comma-separated)", Some(&defaults.tags.join(", ")), |_| {
+        Ok(())
+    })?;
+    let min_rust_version = ui::prompt_text("Minimum Rust version (optional)", None, |v| {
+        if v.is_empty() || semver::Version::parse(v).is_ok() {
+            Ok(())
+        } else {
+            Err("Use a semantic version such as 1.75.0".to_string())
+        }
+    })?;
+
+    let mut parameters = HashMap::new();
+    println!("\n{} Parameters", "⚙️".cyan());
+    while ui::prompt_confirm("Add a parameter?", parameters.is_empty())? {
+        let (name, param) = prompt_parameter(&parameters)?;
+        parameters.insert(name, param);
+    }
+
+    println!("\n{} File rules", "📁".cyan());
+    let include = ui::prompt_text(
+        "Include globs (comma-separated, empty for everything)",
+        None,
+        |v| check_globs(v),
+    )?;
+    let exclude = ui::prompt_text(
+        "Exclude globs (comma-separated, optional)",
+        None,
+        |v| check_globs(v),
+    )?;
+    let raw = ui::prompt_text(
+        "Globs copied without Liquid, e.g. .github/workflows/** (optional)",
+        None,
+        |v| check_globs(v),
+    )?;
+    let files = FileRules {
+        include: split_list(&include),
+        exclude: split_list(&exclude),
+        raw: split_list(&raw),
+    };
+
+    Ok(TemplateSchema {
+        template: TemplateMetadata {
+            name: name.trim().to_string(),
+            description: description.trim().to_string(),
+            version,
+            authors: split_list(&authors),
+            repository,
+            tags: split_list(&tags),
+            min_rust_version: Some(min_rust_version).filter(|v| !v.is_empty()),
+            min_x402_cli_version: None,
+        },
+        parameters: Some(parameters).filter(|p| !p.is_empty()),
+        files: Some(files)
+            .filter(|f| !f.include.is_empty() || !f.exclude.is_empty() || !f.raw.is_empty()),
+    })
+}
+
+/// Ask for one parameter's name, type, description and default
+fn prompt_parameter(existing: &HashMap<String, Parameter>) -> Result<(String, Parameter)> {
+    let name = ui::prompt_text("Parameter name", None, |v| check_parameter_name(v, existing))?;
+    let kind = ui::prompt_choice("Type", &["string", "boolean", "enum"], 0)?;
+    let description = ui::prompt_text("Description (optional)", None, |_| Ok(()))?;
+    let description = Some(description).filter(|d| !d.is_empty());
+    // The existing value prompts ask for the default, labelled as such
+    let default_label = format!("{} default", name);
+
+    let param = match kind {
+        0 => {
+            let pattern = ui::prompt_text("Regex the value must match (optional)", None, |v| {
+                regex::Regex::new(v).map(|_| ()).map_err(|e| e.to_string())
+            })?;
+            let pattern = Some(pattern).filter(|p| !p.is_empty());
+            let check = Parameter::String {
+                default: String::new(),
+                pattern: pattern.clone(),
+                description: None,
+            };
+            let default = ui::prompt_text("Default value", None, move |v| check.validate(v))?;
+            Parameter::String {
+                default,
+                pattern,
+                description,
+            }
+        }
+        1 => {
+            let proto = Parameter::Boolean {
+                default: true,
+                description: None,
+            };
+            let default = ui::prompt_for_parameter(&default_label, &proto)?;
+            Parameter::Boolean {
+                default: default == true.into(),
+                description,
+            }
+        }
+        _ => {
+            let choices = ui::prompt_text("Choices (comma-separated)", None, |v| {
+                match split_list(v).len() {
+                    0 | 1 => Err("Give at least two choices".to_string()),
+                    _ => Ok(()),
+                }
+            })?;
+            let choices = split_list(&choices);
+            let proto = Parameter::Enum {
+                default: choices[0].clone(),
+                choices: choices.clone(),
+                description: None,
+            };
+            let default = ui::prompt_for_parameter(&default_label, &proto)?;
+            Parameter::Enum {
+                choices,
+                default: default.to_string(),
+                description,
+            }
+        }
+    };
+
+    Ok((name, param))
+}
+
+pub(super) fn check_parameter_name(
+    name: &str,
+    existing: &HashMap<String, Parameter>,
+) -> std::result::Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        Err("Use snake_case: lowercase letters, digits and underscores".to_string())
+    } else if BUILTIN_VARIABLES.contains(&name) {
+        Err(format!("'{}' is a built-in variable", name))
+    } else if existing.contains_key(name) {
+        Err(format!("'{}' is already declared", name))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_globs(value: &str) -> std::result::Result<(), String> {
+    for pattern in split_list(value) {
+        glob::Pattern::new(&pattern).map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?;
+    }
+    Ok(())
+}
+
+/// Split a comma-separated answer, dropping empty entries
+pub(super) fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+pub(super) fn default_author() -> String {
+    let name = whoami::realname();
+    if name.trim().is_empty() {
+        whoami::username()
+    } else {
+        name
+    }
+}
+
+/// A minimal crate whose `main.rs` uses every parameter, so the template
+/// passes `validate` and `test` straight away
+fn sample_files(schema: &TemplateSchema) -> Vec<(&'static str, String)> {
+    let cargo_toml = "[package]\n\
+                      name = \"{{ project_name }}\"\n\
+                      version = \"0.1.0\"\n\
+                      edition = \"2021\"\n\
+                      authors = [\"{{ author }}\"]\n\
+                      \n\
+                      [dependencies]\n"
+        .to_string();
+
+    let mut main_rs = String::from(
+        "//! {{ project_name }}, generated with cargo-x402 on {{ date }}\n\
+         \n\
+         fn main() {\n    \
+         println!(\"Hello from {{ project_name }}!\");\n",
+    );
+    let mut names: Vec<(&String, &Parameter)> =
+        schema.parameters.iter().flatten().collect();
+    names.sort_by_key(|(name, _)| *name);
+    for (name, param) in names {
+        let line = match param {
+            Parameter::Boolean { .. } => format!(
+                "    println!(\"{0}: {{% if {0} %}}on{{% else %}}off{{% endif %}}\");\n",
+                name
+            ),
+            Parameter::String { .. } | Parameter::Enum { .. } => {
+                format!("    println!(\"{0}: {{}}\", r#\"{{{{ {0} }}}}\"#);\n", name)
+            }
+        };
+        main_rs.push_str(&line);
+    }
+    main_rs.push_str("}\n");
+
+    vec![("Cargo.toml", cargo_toml), ("src/main.rs", main_rs)]
+}
+
+/// Append usage docs to the README unless it already has them, returning
+/// whether anything was written
+fn add_readme_section(readme: &Path, schema: &TemplateSchema) -> Result<bool> {
+    let existing = match std::fs::read_to_string(readme) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            format!("# {}\n\n{}\n", schema.template.name, schema.template.description)
+        }
+        Err(e) => {
+            return Err(Error::FileSystemError(format!(
+                "Cannot read {}: {}",
+                readme.display(),
+                e
+            )))
+        }
+    };
+    if existing.lines().any(|l| l.trim() == README_HEADING) {
+        return Ok(false);
+    }
+
+    let mut content = existing;
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push('\n');
+    content.push_str(&readme_section(schema));
+    write_file(readme, &content)?;
+    Ok(true)
+}
+
+fn readme_section(schema: &TemplateSchema) -> String {
+    let shorthand = schema
+        .template
+        .repository
+        .trim_start_matches("https://github.com/")
+        .trim_end_matches('/')
+        .trim_end_matches(".git");
+
+    let mut section = format!(
+        "{}\n\n\
+         Create a project from this template with \
+         [cargo-x402](https://github.com/cryptopatrick/cargo-x402):\n\n\
+         ```bash\n\
+         cargo x402 create --template {}\n\
+         ```\n",
+        README_HEADING, shorthand
+    );
+
+    let mut params: Vec<(&String, &Parameter)> = schema.parameters.iter().flatten().collect();
+    if params.is_empty() {
+        return section;
+    }
+    params.sort_by_key(|(name, _)| *name);
+
+    section.push_str("\n| Parameter | Type | Default | Description |\n");
+    section.push_str("|-----------|------|---------|-------------|\n");
+    for (name, param) in params {
+        let (kind, description) = match param {
+            Parameter::String { description, .. } => ("string".to_string(), description),
+            Parameter::Boolean { description, .. } => ("boolean".to_string(), description),
+            Parameter::Enum {
+                choices,
+                description,
+                ..
+            } => (format!("one of {}", choices.join(", ")), description),
+        };
+        section.push_str(&format!(
+            "| `{}` | {} | `{}` | {} |\n",
+            name,
+            kind,
+            param.default_value(),
+            description.as_deref().unwrap_or("")
+        ));
+    }
+    section.push_str("\nSet them with `--define NAME=VALUE`, or answer the prompts.\n");
+    section
+}
+
+pub(super) fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            Error::FileSystemError(format!("Cannot create {}: {}", parent.display(), e))
+        })?;
+    }
+    std::fs::write(path, content)
+        .map_err(|e| Error::FileSystemError(format!("Cannot write {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_example_schema_is_valid() {
+        let schema = example_schema("My_Template");
+        assert!(Validator::validate_schema(&schema).is_ok());
+        assert!(schema.template.repository.ends_with("/my-template"));
+        assert_eq!(schema.parameters.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_check_parameter_name() {
+        let existing = HashMap::new();
+        assert!(check_parameter_name("enable_docker", &existing).is_ok());
+        assert!(check_parameter_name("Enable", &existing).is_err());
+        assert!(check_parameter_name("db-type", &existing).is_err());
+        assert!(check_parameter_name("", &existing).is_err());
+        assert!(check_parameter_name("project_name", &existing).is_err());
+    }
+
+    #[test]
+    fn test_readme_section_added_once() {
+        let dir = TempDir::new().unwrap();
+        let readme = dir.path().join("README.md");
+        std::fs::write(&readme, "# Existing\n\nSome docs").unwrap();
+        let schema = example_schema("demo");
+
+        assert!(add_readme_section(&readme, &schema).unwrap());
+        assert!(!add_readme_section(&readme, &schema).unwrap());
+
+        let content = std::fs::read_to_string(&readme).unwrap();
+        assert!(content.starts_with("# Existing\n\nSome docs\n\n## Using this template"));
+        assert_eq!(content.matches(README_HEADING).count(), 1);
+        assert!(content.contains("| `network` | one of base-sepolia, base | `base-sepolia` |"));
+    }
+}
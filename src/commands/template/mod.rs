@@ -0,0 +1,7 @@
+//! Tools for template authors (`cargo-x402 template ...`)
+//!
+//! - [`init`]: Scaffold x402.toml, sample files and README docs for a new template
+//! - [`extract`]: Turn a working project into a template
+
+pub mod extract;
+pub mod init;
@@ -0,0 +1,314 @@
+//! Render a local template with each combination of its parameters and
+//! check that every result builds
+
+use super::create::normalize_project_name;
+use crate::error::{Error, Result};
+use crate::schema::{ParameterValues, Validator};
+use crate::template::matrix::ParameterMatrix;
+use crate::template::render::builtin_values;
+use crate::template::{FileFilter, Renderer};
+use colored::*;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Command run in each rendered project unless `--check` says otherwise
+pub const DEFAULT_CHECK: &str = "cargo check --offline";
+
+/// Most combinations run unless `--max-combinations` says otherwise
+pub const DEFAULT_MAX_COMBINATIONS: usize = 32;
+
+/// Lines of output shown for each failed combination
+const FAILURE_TAIL_LINES: usize = 20;
+
+/// Options for the test command
+#[derive(Debug)]
+pub struct TestOptions {
+    /// Template directory
+    pub path: std::path::PathBuf,
+
+    /// Shell command run inside each rendered project
+    pub check: String,
+
+    /// Run this many randomly chosen combinations instead of all of them
+    pub sample: Option<usize>,
+
+    /// Never run more combinations than this; larger matrices are sampled
+    pub max_combinations: usize,
+
+    /// Seed for choosing sampled combinations, to repeat a run
+    pub seed: Option<u64>,
+}
+
+impl Default for TestOptions {
+    fn default() -> Self {
+        Self {
+            path: ".".into(),
+            check: DEFAULT_CHECK.to_string(),
+            sample: None,
+            max_combinations: DEFAULT_MAX_COMBINATIONS,
+            seed: None,
+        }
+    }
+}
+
+/// How one combination fared
+enum Outcome {
+    Passed(Duration),
+    Failed {
+        elapsed: Duration,
+        /// Why it failed: the check's output, or the render error
+        output: String,
+    },
+}
+
+/// Execute the test command
+///
+/// Fails when any combination does not render or its check exits non-zero.
+pub fn execute(options: TestOptions) -> Result<()> {
+    let path = options.path.as_path();
+    if !path.is_dir() {
+        return Err(Error::FileSystemError(format!(
+            "{} is not a directory",
+            path.display()
+        )));
+    }
+    let schema_path = path.join("x402.toml");
+    if !schema_path.exists() {
+        return Err(Error::InvalidSchema(
+            "Template does not contain x402.toml".to_string(),
+        ));
+    }
+    let schema = Validator::load_and_validate(&schema_path)?;
+
+    let empty = HashMap::new();
+    let matrix = ParameterMatrix::new(schema.parameters.as_ref().unwrap_or(&empty));
+    let total = matrix.combinations();
+    let limit = options
+        .sample
+        .unwrap_or(options.max_combinations)
+        .min(options.max_combinations);
+    let seed = options.seed.unwrap_or_else(random_seed);
+    let indices = matrix.sample(limit, seed);
+    if indices.is_empty() {
+        // Passing without checking anything would hide a broken template
+        return Err(Error::ParameterError(
+            "--sample and --max-combinations must be at least 1".to_string(),
+        ));
+    }
+
+    if (indices.len() as u128) < total {
+        println!(
+            "\n{} Testing {} of {} combinations (sampled with --seed {})",
+            "🧪".cyan(),
+            indices.len(),
+            total,
+            seed
+        );
+    } else {
+        println!("\n{} Testing {} combinations", "🧪".cyan(), total);
+    }
+    println!("{} {}", "Check:".dimmed(), options.check);
+
+    let mut filter = FileFilter::new(schema.files.as_ref())?;
+    filter = filter.for_working_copy(path)?;
+
+    let project_name = path
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| normalize_project_name(&n.to_string_lossy())))
+        .unwrap_or_else(|| "x402-project".to_string());
+    let builtins = builtin_values(&project_name);
+
+    let scratch = tempfile::TempDir::new()
+        .map_err(|e| Error::FileSystemError(format!("Cannot create temp directory: {}", e)))?;
+    // One target directory for every combination, so dependencies build once
+    let target_dir = scratch.path().join("target");
+
+    let names = matrix.names();
+    let widths: Vec<usize> = names
+        .iter()
+        .enumerate()
+        .map(|(column, name)| {
+            indices
+                .iter()
+                .map(|i| matrix.values(*i)[column].to_string().len())
+                .chain(std::iter::once(name.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    println!("{}", "─".repeat(80));
+    let mut header = format!("{:<5}", "#");
+    for (name, width) in names.iter().zip(&widths) {
+        header.push_str(&format!(" {:<width$}", name, width = width));
+    }
+    println!("{} RESULT", header.bold());
+
+    let mut failures = Vec::new();
+    for (row, index) in indices.iter().enumerate() {
+        let mut parameters = builtins.clone();
+        parameters.extend(matrix.combination(*index));
+
+        let output_dir = scratch.path().join(row.to_string()).join(&project_name);
+        let outcome = run_combination(
+            path,
+            &output_dir,
+            &parameters,
+            &filter,
+            &options.check,
+            &target_dir,
+        );
+        let _ = std::fs::remove_dir_all(output_dir.parent().unwrap_or(&output_dir));
+
+        let mut line = format!("{:<5}", row + 1);
+        for (value, width) in matrix.values(*index).iter().zip(&widths) {
+            line.push_str(&format!(" {:<width$}", value.to_string(), width = width));
+        }
+        match &outcome {
+            Outcome::Passed(elapsed) => println!(
+                "{} {} {}",
+                line,
+                "pass".green(),
+                format_elapsed(*elapsed).dimmed()
+            ),
+            Outcome::Failed { elapsed, .. } => println!(
+                "{} {} {}",
+                line,
+                "FAIL".red().bold(),
+                format_elapsed(*elapsed).dimmed()
+            ),
+        }
+        if let Outcome::Failed { output, .. } = outcome {
+            failures.push((row + 1, *index, output));
+        }
+    }
+    println!("{}", "─".repeat(80));
+
+    for (row, index, output) in &failures {
+        println!("\n{} combination {}", "Failed:".red().bold(), row);
+        let defines: Vec<String> = names
+            .iter()
+            .zip(matrix.values(*index))
+            .map(|(name, value)| format!("-D {}={}", name, value))
+            .collect();
+        println!(
+            "{} cargo-x402 create --template {} --defaults --name {} {}",
+            "Reproduce:".dimmed(),
+            path.display(),
+            project_name,
+            defines.join(" ")
+        );
+        for line in tail(output, FAILURE_TAIL_LINES) {
+            println!("  {}", line);
+        }
+    }
+
+    let passed = indices.len() - failures.len();
+    if failures.is_empty() {
+        println!(
+            "\n{} All {} combinations passed",
+            "✅".green(),
+            indices.len()
+        );
+        return Ok(());
+    }
+    println!(
+        "\n{} {} passed, {} failed",
+        "❌".red(),
+        passed,
+        failures.len()
+    );
+    Err(Error::ValidationError {
+        field: path.display().to_string(),
+        message: format!(
+            "{} of {} combinations failed",
+            failures.len(),
+            indices.len()
+        ),
+    })
+}
+
+/// Render one combination and run the check command in it
+fn run_combination(
+    template_dir: &Path,
+    output_dir: &Path,
+    parameters: &ParameterValues,
+    filter: &FileFilter,
+    check: &str,
+    target_dir: &Path,
+) -> Outcome {
+    let started = Instant::now();
+    if let Err(e) = Renderer::render_with_filter(template_dir, output_dir, parameters, filter) {
+        return Outcome::Failed {
+            elapsed: started.elapsed(),
+            output: e.to_string(),
+        };
+    }
+
+    let result = shell(check)
+        .current_dir(output_dir)
+        .env("CARGO_TARGET_DIR", target_dir)
+        .output();
+    let elapsed = started.elapsed();
+    match result {
+        Ok(output) if output.status.success() => Outcome::Passed(elapsed),
+        Ok(output) => Outcome::Failed {
+            elapsed,
+            output: format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        },
+        Err(e) => Outcome::Failed {
+            elapsed,
+            output: format!("Cannot run '{}': {}", check, e),
+        },
+    }
+}
+
+/// Run a command line through the platform shell, so quoting and `&&` work
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    format!("{:.1}s", elapsed.as_secs_f64())
+}
+
+/// The last `count` non-empty lines of some output
+fn tail(output: &str, count: usize) -> Vec<&str> {
+    let lines: Vec<&str> = output.lines().filter(|l| !l.trim().is_empty()).collect();
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_keeps_last_non_empty_lines() {
+        let output = "one\n\ntwo\nthree\n  \nfour\n";
+        assert_eq!(tail(output, 2), vec!["three", "four"]);
+        assert_eq!(tail(output, 10), vec!["one", "two", "three", "four"]);
+        assert!(tail("", 3).is_empty());
+    }
+}
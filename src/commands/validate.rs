@@ -0,0 +1,62 @@
+//! Lint a local template
+
+use crate::error::{Error, Result};
+use crate::template::lint::{LintIssue, Linter};
+use colored::*;
+use std::path::Path;
+
+/// Execute the validate command
+///
+/// Fails when the template has errors, or warnings too with `strict`.
+pub fn execute(path: &Path, strict: bool) -> Result<()> {
+    if !path.is_dir() {
+        return Err(Error::FileSystemError(format!(
+            "{} is not a directory",
+            path.display()
+        )));
+    }
+
+    let report = Linter::lint(path)?;
+
+    for issue in report.errors() {
+        print_issue("error", issue);
+    }
+    for issue in report.warnings() {
+        print_issue("warning", issue);
+    }
+
+    let errors = report.errors().count();
+    let warnings = report.warnings().count();
+    let failed = report.has_errors() || (strict && warnings > 0);
+
+    if report.issues.is_empty() {
+        println!(
+            "{} Template is valid ({} files checked)",
+            "✅".green(),
+            report.files_checked
+        );
+    } else if !failed {
+        println!(
+            "{} Template is valid with {} warnings ({} files checked)",
+            "⚠️".yellow(),
+            warnings,
+            report.files_checked
+        );
+    }
+
+    if failed {
+        return Err(Error::ValidationError {
+            field: path.display().to_string(),
+            message: format!("{} errors, {} warnings", errors, warnings),
+        });
+    }
+    Ok(())
+}
+
+fn print_issue(label: &str, issue: &LintIssue) {
+    let label = match label {
+        "error" => label.red().bold(),
+        _ => label.yellow().bold(),
+    };
+    println!("{}: {}", label, issue);
+}
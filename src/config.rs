@@ -0,0 +1,155 @@
+//! User configuration file
+//!
+//! Settings are read from `~/.config/cargo-x402/config.toml` (or the file
+//! named by `X402_CONFIG`). A missing file means "all defaults".
+//!
+//! ```toml
+//! # Host used for `owner/repo` shorthands and `list`
+//! default_host = "github.example.com"
+//!
+//! # Cap on templates fetched by `list` (default 500)
+//! max_templates = 200
+//!
+//! # Hours before the cached template list is refreshed (default 1)
+//! cache_ttl_hours = 24
+//!
+//! [hosts."github.example.com"]
+//! api_url = "https://github.example.com/api/v3"
+//! web_url = "https://github.example.com"
+//! token = "ghp_..."
+//! ```
+
+use crate::discovery::cache::DEFAULT_TTL_HOURS;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the config file location
+pub const CONFIG_ENV: &str = "X402_CONFIG";
+
+const CONFIG_DIR_NAME: &str = "cargo-x402";
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Public GitHub, used when no other host is configured
+pub const DEFAULT_HOST: &str = "github.com";
+
+/// Contents of the cargo-x402 config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// GitHub host used for `owner/repo` shorthands and discovery
+    #[serde(default)]
+    pub default_host: Option<String>,
+
+    /// Stop listing templates after this many search results
+    #[serde(default)]
+    pub max_templates: Option<usize>,
+
+    /// Hours before the cached template list is refreshed
+    #[serde(default)]
+    pub cache_ttl_hours: Option<u64>,
+
+    /// Per-host settings, keyed by host name (e.g. `github.example.com`)
+    #[serde(default)]
+    pub hosts: HashMap<String, HostConfig>,
+}
+
+/// Settings for one GitHub or GitHub Enterprise host
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HostConfig {
+    /// REST API base URL (default: `https://api.github.com` for github.com,
+    /// `https://{host}/api/v3` otherwise)
+    #[serde(default)]
+    pub api_url: Option<String>,
+
+    /// Web base URL that repository URLs start with (default: `https://{host}`)
+    #[serde(default)]
+    pub web_url: Option<String>,
+
+    /// Access token for private repositories
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Config {
+    /// Load the config file, returning defaults when it does not exist
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load a config file from an explicit path
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::FileSystemError(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+
+        toml::from_str(&content)
+            .map_err(|e| Error::TomlError(format!("Invalid config {}: {}", path.display(), e)))
+    }
+
+    /// Location of the config file
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV) {
+            return Some(PathBuf::from(path));
+        }
+
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    /// Host used for `owner/repo` shorthands and discovery
+    pub fn default_host(&self) -> &str {
+        self.default_host.as_deref().unwrap_or(DEFAULT_HOST)
+    }
+
+    /// Hours before the cached template list is refreshed
+    pub fn cache_ttl_hours(&self) -> u64 {
+        self.cache_ttl_hours.unwrap_or(DEFAULT_TTL_HOURS)
+    }
+
+    /// Settings for a host, if any are configured
+    pub fn host(&self, name: &str) -> Option<&HostConfig> {
+        self.hosts.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str(
+            r#"
+default_host = "github.example.com"
+cache_ttl_hours = 24
+
+[hosts."github.example.com"]
+api_url = "https://github.example.com/api/v3"
+token = "secret"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.default_host(), "github.example.com");
+        let host = config.host("github.example.com").unwrap();
+        assert_eq!(host.token.as_deref(), Some("secret"));
+        assert_eq!(host.web_url, None);
+        assert_eq!(config.cache_ttl_hours(), 24);
+        assert_eq!(Config::default().default_host(), DEFAULT_HOST);
+        assert_eq!(Config::default().cache_ttl_hours(), DEFAULT_TTL_HOURS);
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_keys() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "defualt_host = \"typo\"\n").unwrap();
+
+        assert!(Config::load_from(&path).is_err());
+    }
+}
@@ -1,147 +1,247 @@
 //! Template discovery caching with TTL
+//!
+//! The cache file carries a schema version. Files from another version, or
+//! that fail to parse, are treated as a miss and overwritten on the next
+//! fetch. Writes go to a temp file that is renamed into place, so concurrent
+//! runs never see a half-written file.
 
+use super::github::SearchResults;
 use super::TemplateInfo;
+use crate::config::Config;
 use crate::error::{Error, Result};
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-const CACHE_DIR_NAME: &str = "x402";
+/// Directory under the user cache directory holding everything cargo-x402 caches
+pub const CACHE_DIR_NAME: &str = "x402";
 const CACHE_FILE_NAME: &str = "templates.json";
-const DEFAULT_TTL_HOURS: i64 = 1;
+
+/// Bumped whenever the shape of [`CachedTemplates`] or [`TemplateInfo`] changes
+pub const CACHE_VERSION: u32 = 1;
+
+/// How long a template list stays fresh unless configured otherwise
+pub const DEFAULT_TTL_HOURS: u64 = 1;
 
 /// Cached template list with timestamp
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedTemplates {
+    /// Schema version the entry was written with (0 for files that predate it)
+    #[serde(default)]
+    pub version: u32,
+
     /// Timestamp when cache was created
     pub last_updated: DateTime<Utc>,
 
     /// Cached template list
     pub templates: Vec<TemplateInfo>,
+
+    /// Number of matching repositories GitHub reported
+    #[serde(default)]
+    pub total_count: usize,
+
+    /// Whether the search stopped at the result cap
+    #[serde(default)]
+    pub truncated: bool,
+
+    /// `ETag` of the search response, for conditional refreshes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+
+    /// `Last-Modified` of the search response, for conditional refreshes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
 }
 
 impl CachedTemplates {
     /// Check if cache is still valid
-    pub fn is_fresh(&self, ttl_hours: i64) -> bool {
-        let now = Utc::now();
-        let ttl = Duration::hours(ttl_hours);
-        now.signed_duration_since(self.last_updated) < ttl
+    pub fn is_fresh(&self, ttl_hours: u64) -> bool {
+        // Whole hours, so that huge TTLs cannot overflow a `Duration`
+        u64::try_from(self.age_hours()).map_or(true, |age| age < ttl_hours)
     }
 
     /// Create new cache with current templates
     pub fn new(templates: Vec<TemplateInfo>) -> Self {
         Self {
+            version: CACHE_VERSION,
             last_updated: Utc::now(),
+            total_count: templates.len(),
             templates,
+            truncated: false,
+            etag: None,
+            last_modified: None,
         }
     }
+
+    /// Hours since the entry was written
+    pub fn age_hours(&self) -> i64 {
+        Utc::now()
+            .signed_duration_since(self.last_updated)
+            .num_hours()
+    }
+
+    /// Create a cache entry from a topic search
+    pub fn from_search(results: SearchResults) -> Self {
+        Self {
+            version: CACHE_VERSION,
+            last_updated: Utc::now(),
+            templates: results.templates,
+            total_count: results.total_count,
+            truncated: results.truncated,
+            etag: results.etag,
+            last_modified: results.last_modified,
+        }
+    }
+
+    /// Mark the entry as up to date, after GitHub reported no changes
+    pub fn touch(&mut self) {
+        self.last_updated = Utc::now();
+    }
 }
 
 /// Cache for template discovery results
 pub struct Cache {
     cache_dir: PathBuf,
-    ttl_hours: i64,
+    ttl_hours: u64,
 }
 
 impl Cache {
-    /// Create a new cache instance
+    /// Create a cache in the user's cache directory with the default TTL
     pub fn new() -> Result<Self> {
-        let cache_dir = Self::cache_directory()?;
-        Ok(Self {
-            cache_dir,
+        Ok(Self::with_dir(Self::cache_directory()?))
+    }
+
+    /// Create a cache in a custom directory, created on first write
+    pub fn with_dir(cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
             ttl_hours: DEFAULT_TTL_HOURS,
-        })
+        }
+    }
+
+    /// Create a cache in the user's cache directory with the configured TTL
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self::new()?.with_ttl_hours(config.cache_ttl_hours()))
+    }
+
+    /// Use a different TTL
+    pub fn with_ttl_hours(mut self, ttl_hours: u64) -> Self {
+        self.ttl_hours = ttl_hours;
+        self
     }
 
     /// Get cache directory path
     fn cache_directory() -> Result<PathBuf> {
-        let cache_dir = dirs::cache_dir()
+        Ok(dirs::cache_dir()
             .ok_or_else(|| Error::CacheError("Cannot determine cache directory".to_string()))?
-            .join(CACHE_DIR_NAME);
+            .join(CACHE_DIR_NAME))
+    }
 
-        // Create cache directory if it doesn't exist
-        std::fs::create_dir_all(&cache_dir)
-            .map_err(|e| Error::CacheError(format!("Cannot create cache directory: {}", e)))?;
+    /// Directory holding the cache
+    pub fn dir(&self) -> &Path {
+        &self.cache_dir
+    }
 
-        Ok(cache_dir)
+    /// Hours after which the cached list is considered stale
+    pub fn ttl_hours(&self) -> u64 {
+        self.ttl_hours
     }
 
     /// Get cache file path
-    fn cache_file_path(&self) -> PathBuf {
+    pub fn cache_file_path(&self) -> PathBuf {
         self.cache_dir.join(CACHE_FILE_NAME)
     }
 
     /// Load templates from cache if fresh
     pub fn load(&self) -> Result<Option<Vec<TemplateInfo>>> {
-        let cache_path = self.cache_file_path();
-
-        // If cache file doesn't exist, return None
-        if !cache_path.exists() {
-            return Ok(None);
-        }
-
-        let content = std::fs::read_to_string(&cache_path)
-            .map_err(|e| Error::CacheError(format!("Cannot read cache: {}", e)))?;
+        Ok(self.load_entry()?.map(|cached| cached.templates))
+    }
 
-        let cached: CachedTemplates = serde_json::from_str(&content)
-            .map_err(|e| Error::CacheError(format!("Invalid cache format: {}", e)))?;
+    /// Load the whole cache entry if fresh
+    pub fn load_entry(&self) -> Result<Option<CachedTemplates>> {
+        Ok(self.read()?.filter(|cached| cached.is_fresh(self.ttl_hours)))
+    }
 
-        // Check if cache is still fresh
-        if cached.is_fresh(self.ttl_hours) {
-            Ok(Some(cached.templates))
-        } else {
-            Ok(None)
-        }
+    /// Read the cache entry regardless of its age
+    ///
+    /// A corrupt entry, or one written by another version, reads as `None`.
+    pub fn read(&self) -> Result<Option<CachedTemplates>> {
+        let content = match std::fs::read(self.cache_file_path()) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::CacheError(format!("Cannot read cache: {}", e))),
+        };
+
+        Ok(serde_json::from_slice::<CachedTemplates>(&content)
+            .ok()
+            .filter(|cached| cached.version == CACHE_VERSION))
     }
 
     /// Save templates to cache
+    #[allow(dead_code)] // Intentionally public for library users
     pub fn save(&self, templates: &[TemplateInfo]) -> Result<()> {
-        let cached = CachedTemplates::new(templates.to_vec());
+        self.store(&CachedTemplates::new(templates.to_vec()))
+    }
 
+    /// Save a cache entry, stamped with the current schema version
+    pub fn store(&self, cached: &CachedTemplates) -> Result<()> {
+        let cached = CachedTemplates {
+            version: CACHE_VERSION,
+            ..cached.clone()
+        };
         let content = serde_json::to_string_pretty(&cached)
             .map_err(|e| Error::CacheError(format!("Cannot serialize cache: {}", e)))?;
 
-        let cache_path = self.cache_file_path();
-        std::fs::write(&cache_path, content)
-            .map_err(|e| Error::CacheError(format!("Cannot write cache: {}", e)))?;
+        write_atomic(&self.cache_file_path(), content.as_bytes())
+    }
 
-        Ok(())
+    /// Delete the cached template list, returning whether there was one
+    pub fn clear(&self) -> Result<bool> {
+        match std::fs::remove_file(self.cache_file_path()) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(Error::CacheError(format!("Cannot delete cache: {}", e))),
+        }
     }
 
     /// Get cache age in hours
     pub fn age_hours(&self) -> Result<Option<i64>> {
-        let cache_path = self.cache_file_path();
-
-        if !cache_path.exists() {
-            return Ok(None);
-        }
-
-        let content = std::fs::read_to_string(&cache_path)
-            .map_err(|e| Error::CacheError(format!("Cannot read cache: {}", e)))?;
-
-        let cached: CachedTemplates = serde_json::from_str(&content)
-            .map_err(|e| Error::CacheError(format!("Invalid cache format: {}", e)))?;
-
-        let age = Utc::now().signed_duration_since(cached.last_updated);
-        Ok(Some(age.num_hours()))
+        Ok(self.read()?.map(|cached| cached.age_hours()))
     }
 }
 
+/// Replace a file's contents all at once: readers see either the old file or
+/// the new one, never a partial write
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    use std::io::Write;
+
+    let dir = path
+        .parent()
+        .ok_or_else(|| Error::CacheError(format!("Invalid cache path {}", path.display())))?;
+    std::fs::create_dir_all(dir)
+        .map_err(|e| Error::CacheError(format!("Cannot create cache directory: {}", e)))?;
+
+    let mut temp = tempfile::NamedTempFile::new_in(dir)
+        .map_err(|e| Error::CacheError(format!("Cannot create temp file: {}", e)))?;
+    temp.write_all(contents)
+        .and_then(|_| temp.as_file().sync_all())
+        .map_err(|e| Error::CacheError(format!("Cannot write cache: {}", e)))?;
+    temp.persist(path)
+        .map_err(|e| Error::CacheError(format!("Cannot write cache: {}", e)))?;
+    Ok(())
+}
+
 impl Default for Cache {
     fn default() -> Self {
-        Self::new().unwrap_or_else(|_| {
-            // Fallback in case cache directory fails
-            Self {
-                cache_dir: PathBuf::from("/tmp/x402-cache"),
-                ttl_hours: DEFAULT_TTL_HOURS,
-            }
-        })
+        // Fallback in case cache directory fails
+        Self::new().unwrap_or_else(|_| Self::with_dir("/tmp/x402-cache"))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Duration;
 
     #[test]
     fn test_cached_templates_is_fresh() {
@@ -162,5 +262,85 @@ mod tests {
 
         assert!(!cached.is_fresh(1)); // Should be stale with 1-hour TTL
         assert!(cached.is_fresh(3)); // Should still be fresh with 3-hour TTL
+        assert!(!cached.is_fresh(0));
+        assert!(cached.is_fresh(u64::MAX));
+    }
+
+    #[test]
+    fn test_cache_in_custom_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = Cache::with_dir(dir.path().join("x402")).with_ttl_hours(2);
+        assert!(cache.read().unwrap().is_none());
+
+        let mut cached = CachedTemplates::new(vec![]);
+        cached.last_updated = Utc::now() - Duration::hours(3);
+        cache.store(&cached).unwrap();
+
+        assert!(cache.read().unwrap().is_some());
+        assert!(cache.load_entry().unwrap().is_none());
+        assert_eq!(cache.age_hours().unwrap(), Some(3));
+
+        assert!(cache.clear().unwrap());
+        assert!(!cache.clear().unwrap());
+        assert!(cache.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_corrupt_cache_is_a_miss() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = Cache::with_dir(dir.path());
+
+        std::fs::write(cache.cache_file_path(), r#"{ "last_updated": "2025-01-"#).unwrap();
+        assert!(cache.read().unwrap().is_none());
+        assert!(cache.load().unwrap().is_none());
+
+        // Rewritten on the next store
+        cache.store(&CachedTemplates::new(vec![])).unwrap();
+        assert!(cache.read().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_version_mismatch_is_a_miss() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = Cache::with_dir(dir.path());
+
+        // Written before the cache had a version
+        std::fs::write(
+            cache.cache_file_path(),
+            r#"{ "last_updated": "2025-01-01T00:00:00Z", "templates": [] }"#,
+        )
+        .unwrap();
+        assert!(cache.read().unwrap().is_none());
+
+        let mut future = serde_json::to_value(CachedTemplates::new(vec![])).unwrap();
+        future["version"] = (CACHE_VERSION + 1).into();
+        std::fs::write(cache.cache_file_path(), future.to_string()).unwrap();
+        assert!(cache.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_store_leaves_no_temp_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = Cache::with_dir(dir.path());
+
+        let mut stale = CachedTemplates::new(vec![]);
+        stale.version = 0;
+        cache.store(&stale).unwrap();
+        cache.store(&CachedTemplates::new(vec![])).unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(cache.read().unwrap().unwrap().version, CACHE_VERSION);
+    }
+
+    #[test]
+    fn test_entry_without_truncation_fields() {
+        // Caches written before pagination lack total_count and truncated
+        let cached: CachedTemplates =
+            serde_json::from_str(r#"{ "last_updated": "2025-01-01T00:00:00Z", "templates": [] }"#)
+                .unwrap();
+
+        assert!(!cached.truncated);
+        assert_eq!(cached.total_count, 0);
     }
 }
@@ -1,18 +1,60 @@
 //! GitHub API integration for template discovery
 
-use super::TemplateInfo;
+use super::cache::CachedTemplates;
+use super::{http, GitHubHost, TemplateInfo};
+use crate::config::Config;
 use crate::error::{Error, Result};
 use serde::Deserialize;
 
-const GITHUB_API_BASE: &str = "https://api.github.com";
 const X402_TOPIC: &str = "x402-template";
 
+/// Results per search page (the API maximum)
+const PER_PAGE: usize = 100;
+
+/// GitHub never returns more than this many results for one search
+const SEARCH_RESULT_LIMIT: usize = 1000;
+
+/// Templates fetched by default before the listing is cut off
+pub const DEFAULT_MAX_RESULTS: usize = 500;
+
+/// Search pages requested at the same time
+const PAGE_CONCURRENCY: usize = 4;
+
 /// GitHub API response for repository search
 #[derive(Debug, Deserialize)]
 struct SearchResponse {
+    #[serde(default)]
+    total_count: usize,
     items: Vec<RepositoryInfo>,
 }
 
+/// Templates found by a topic search
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchResults {
+    /// De-duplicated templates, most starred first
+    pub templates: Vec<TemplateInfo>,
+
+    /// Number of matching repositories GitHub reported
+    pub total_count: usize,
+
+    /// Whether matches were left out because of the result cap
+    pub truncated: bool,
+
+    /// `ETag` of the first page, for conditional requests
+    pub etag: Option<String>,
+
+    /// `Last-Modified` of the first page, for conditional requests
+    pub last_modified: Option<String>,
+}
+
+/// One page of search results with the headers we care about
+struct Page {
+    response: SearchResponse,
+    link: Option<String>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
 /// Repository information from GitHub API
 #[derive(Debug, Deserialize)]
 struct RepositoryInfo {
@@ -23,6 +65,8 @@ struct RepositoryInfo {
     stargazers_count: u32,
     language: Option<String>,
     topics: Vec<String>,
+    #[serde(default)]
+    default_branch: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -30,35 +74,211 @@ struct Owner {
     login: String,
 }
 
+impl From<RepositoryInfo> for TemplateInfo {
+    fn from(repo: RepositoryInfo) -> Self {
+        let description = repo.description.unwrap_or_default();
+        TemplateInfo {
+            name: if description.is_empty() {
+                repo.name.clone()
+            } else {
+                description.clone()
+            },
+            description,
+            url: repo.html_url,
+            owner: repo.owner.login,
+            repo: repo.name,
+            stars: repo.stargazers_count,
+            language: repo.language.unwrap_or_else(|| "Unknown".to_string()),
+            topics: repo.topics,
+            default_branch: repo.default_branch,
+        }
+    }
+}
+
 /// GitHub-based template discoverer
 pub struct GitHubDiscovery {
     client: reqwest::Client,
+    host: GitHubHost,
+    max_results: usize,
 }
 
 impl GitHubDiscovery {
-    /// Create a new GitHub discoverer
+    /// Create a new GitHub discoverer for anonymous access to github.com
     pub fn new() -> Self {
+        Self::for_host(GitHubHost::public())
+    }
+
+    /// Create a discoverer that talks to a different API base URL, such as a
+    /// GitHub mirror or a local mock server
+    #[allow(dead_code)] // Intentionally public for library users
+    pub fn with_base_url(api_url: &str) -> Self {
+        Self::for_host(GitHubHost::with_api_url(api_url))
+    }
+
+    /// Create a discoverer for a GitHub or GitHub Enterprise host,
+    /// authenticating with the host's token when it has one
+    pub fn for_host(host: GitHubHost) -> Self {
         Self {
             client: reqwest::Client::new(),
+            host,
+            max_results: DEFAULT_MAX_RESULTS,
         }
     }
 
+    /// Create a discoverer for the configured default host and result cap
+    pub fn from_config(config: &Config) -> Self {
+        let discovery = Self::for_host(GitHubHost::default_from(config));
+        match config.max_templates {
+            Some(max_results) => discovery.with_max_results(max_results),
+            None => discovery,
+        }
+    }
+
+    /// Stop following search pages once this many templates are found
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = max_results.max(1);
+        self
+    }
+
+    /// The host this discoverer talks to
+    pub fn host(&self) -> &GitHubHost {
+        &self.host
+    }
+
+    /// Build an API request with the common headers and credentials
+    fn get(&self, url: &str, accept: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .get(url)
+            .header("Accept", accept)
+            .header("User-Agent", "cargo-x402");
+        self.host.authorize(request)
+    }
+
     /// Discover templates from GitHub
+    #[allow(dead_code)] // Intentionally public for library users
     pub async fn discover(&self) -> Result<Vec<TemplateInfo>> {
+        Ok(self.search().await?.templates)
+    }
+
+    /// Search for templates, following pagination up to the result cap
+    pub async fn search(&self) -> Result<SearchResults> {
+        self.search_if_modified(None, None)
+            .await?
+            .ok_or_else(|| Error::GitHubApiError("Unexpected 304 Not Modified".to_string()))
+    }
+
+    /// Search for templates unless the results are unchanged
+    ///
+    /// The validators from a previous search are sent with the first page
+    /// request; `None` means GitHub answered `304 Not Modified`, which does
+    /// not count against the rate limit. Otherwise the first page tells us
+    /// how many pages there are and the rest are fetched a few at a time.
+    pub async fn search_if_modified(
+        &self,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+    ) -> Result<Option<SearchResults>> {
+        let mut request = self.search_request(1);
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+        let first = match Self::fetch_page(request).await? {
+            Some(page) => page,
+            None => return Ok(None),
+        };
+
+        let total_count = first.response.total_count;
+        let wanted = total_count.min(SEARCH_RESULT_LIMIT).min(self.max_results);
+        let has_more = first
+            .link
+            .as_deref()
+            .and_then(|l| link_url(l, "next"))
+            .is_some();
+        let last_page = if has_more {
+            wanted.div_ceil(PER_PAGE)
+        } else {
+            1
+        };
+
+        let mut pages = vec![first.response.items];
+        let remaining: Vec<usize> = (2..=last_page).collect();
+        for batch in remaining.chunks(PAGE_CONCURRENCY) {
+            let mut tasks = tokio::task::JoinSet::new();
+            for &page in batch {
+                let request = self.search_request(page);
+                tasks.spawn(async move { (page, Self::fetch_page(request).await) });
+            }
+
+            let mut fetched = Vec::new();
+            while let Some(joined) = tasks.join_next().await {
+                let (page, result) = joined.map_err(|e| {
+                    Error::GitHubApiError(format!("Search request failed: {}", e))
+                })?;
+                let items = result?.map(|p| p.response.items).unwrap_or_default();
+                fetched.push((page, items));
+            }
+            fetched.sort_by_key(|(page, _)| *page);
+            pages.extend(fetched.into_iter().map(|(_, items)| items));
+        }
+
+        // Results can shift between pages while we fetch them
+        let mut seen = std::collections::HashSet::new();
+        let mut templates: Vec<TemplateInfo> = pages
+            .into_iter()
+            .flatten()
+            .map(TemplateInfo::from)
+            .filter(|t| seen.insert((t.owner.to_lowercase(), t.repo.to_lowercase())))
+            .collect();
+        templates.truncate(self.max_results);
+
+        Ok(Some(SearchResults {
+            truncated: total_count > wanted,
+            total_count,
+            templates,
+            etag: first.etag,
+            last_modified: first.last_modified,
+        }))
+    }
+
+    /// Refresh a cached listing, re-using it as-is if GitHub reports that
+    /// nothing changed
+    pub async fn refresh(&self, previous: Option<CachedTemplates>) -> Result<CachedTemplates> {
+        let (etag, last_modified) = match &previous {
+            Some(cached) => (cached.etag.as_deref(), cached.last_modified.as_deref()),
+            None => (None, None),
+        };
+
+        match (self.search_if_modified(etag, last_modified).await?, previous) {
+            (Some(results), _) => Ok(CachedTemplates::from_search(results)),
+            (None, Some(mut previous)) => {
+                previous.touch();
+                Ok(previous)
+            }
+            (None, None) => Err(Error::GitHubApiError(
+                "Unexpected 304 Not Modified".to_string(),
+            )),
+        }
+    }
+
+    fn search_request(&self, page: usize) -> reqwest::RequestBuilder {
         let url = format!(
-            "{}/search/repositories?q=topic:{}&sort=stars&order=desc&per_page=100",
-            GITHUB_API_BASE, X402_TOPIC
+            "{}/search/repositories?q=topic:{}&sort=stars&order=desc&per_page={}&page={}",
+            self.host.api_url, X402_TOPIC, PER_PAGE, page
         );
+        self.get(&url, "application/vnd.github.v3+json")
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Accept", "application/vnd.github.v3+json")
-            .header("User-Agent", "cargo-x402")
-            .send()
-            .await
-            .map_err(|e| Error::GitHubApiError(format!("Failed to fetch templates: {}", e)))?;
+    /// Fetch one search page, or `None` for `304 Not Modified`
+    async fn fetch_page(request: reqwest::RequestBuilder) -> Result<Option<Page>> {
+        let response = http::send(request).await?;
 
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
         if !response.status().is_success() {
             let status = response.status();
             let error_text = response
@@ -71,51 +291,43 @@ impl GitHubDiscovery {
             )));
         }
 
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let link = header(reqwest::header::LINK);
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+
         let search_response: SearchResponse = response
             .json()
             .await
             .map_err(|e| Error::GitHubApiError(format!("Failed to parse response: {}", e)))?;
 
-        let templates = search_response
-            .items
-            .into_iter()
-            .map(|repo| {
-                let description = repo.description.unwrap_or_default();
-                TemplateInfo {
-                    name: if description.is_empty() {
-                        repo.name.clone()
-                    } else {
-                        description.clone()
-                    },
-                    description,
-                    url: repo.html_url,
-                    owner: repo.owner.login,
-                    repo: repo.name,
-                    stars: repo.stargazers_count,
-                    language: repo.language.unwrap_or_else(|| "Unknown".to_string()),
-                    topics: repo.topics,
-                }
-            })
-            .collect();
-
-        Ok(templates)
+        Ok(Some(Page {
+            response: search_response,
+            link,
+            etag,
+            last_modified,
+        }))
     }
 
     /// Get a specific template by owner/repo
     pub async fn get_template(&self, owner: &str, repo: &str) -> Result<TemplateInfo> {
-        let url = format!("{}/repos/{}/{}", GITHUB_API_BASE, owner, repo);
+        let url = format!("{}/repos/{}/{}", self.host.api_url, owner, repo);
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Accept", "application/vnd.github.v3+json")
-            .header("User-Agent", "cargo-x402")
-            .send()
-            .await
-            .map_err(|e| Error::GitHubApiError(format!("Failed to fetch template: {}", e)))?;
+        let response = http::send(self.get(&url, "application/vnd.github.v3+json")).await?;
 
         if !response.status().is_success() {
-            return Err(Error::TemplateNotFound(format!("{}/{}", owner, repo)));
+            // GitHub answers 404 for private repositories it won't show us
+            let name = match self.host.token {
+                Some(_) => format!("{}/{}", owner, repo),
+                None => format!("{}/{} ({})", owner, repo, self.host.missing_token_hint()),
+            };
+            return Err(Error::TemplateNotFound(name));
         }
 
         let repo_info: RepositoryInfo = response
@@ -123,24 +335,126 @@ impl GitHubDiscovery {
             .await
             .map_err(|e| Error::GitHubApiError(format!("Failed to parse response: {}", e)))?;
 
-        let description = repo_info.description.unwrap_or_default();
-        Ok(TemplateInfo {
-            name: if description.is_empty() {
-                repo_info.name.clone()
-            } else {
-                description.clone()
-            },
-            description,
-            url: repo_info.html_url,
-            owner: repo_info.owner.login,
-            repo: repo_info.name,
-            stars: repo_info.stargazers_count,
-            language: repo_info.language.unwrap_or_else(|| "Unknown".to_string()),
-            topics: repo_info.topics,
-        })
+        Ok(repo_info.into())
+    }
+
+    /// Fetch a single file from a repository through the contents API,
+    /// at `reference` or the default branch
+    pub async fn get_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        reference: Option<&str>,
+    ) -> Result<String> {
+        let url = format!(
+            "{}/repos/{}/{}/contents/{}",
+            self.host.api_url, owner, repo, path
+        );
+        let mut request = self.get(&url, "application/vnd.github.raw");
+        if let Some(reference) = reference {
+            request = request.query(&[("ref", reference)]);
+        }
+
+        let response = http::send(request).await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            let at = reference.map(|r| format!("@{}", r)).unwrap_or_default();
+            let name = match self.host.token {
+                Some(_) => format!("{}/{}{} (no {})", owner, repo, at, path),
+                None => format!(
+                    "{}/{}{} (no {}; {})",
+                    owner,
+                    repo,
+                    at,
+                    path,
+                    self.host.missing_token_hint()
+                ),
+            };
+            return Err(Error::TemplateNotFound(name));
+        }
+        if !status.is_success() {
+            return Err(Error::GitHubApiError(format!(
+                "GitHub API returned {} while fetching {}",
+                status, path
+            )));
+        }
+
+        response
+            .text()
+            .await
+            .map_err(|e| Error::GitHubApiError(format!("Failed to read response: {}", e)))
+    }
+
+    /// Resolve a branch, tag or commit SHA to a full commit SHA
+    pub async fn resolve_commit(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+    ) -> Result<String> {
+        let url = format!(
+            "{}/repos/{}/{}/commits/{}",
+            self.host.api_url, owner, repo, reference
+        );
+
+        let response = http::send(self.get(&url, "application/vnd.github.sha")).await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND
+            || status == reqwest::StatusCode::UNPROCESSABLE_ENTITY
+        {
+            return Err(Error::ValidationError {
+                field: "ref".to_string(),
+                message: format!(
+                    "'{}' is not a branch, tag or commit of {}/{}",
+                    reference, owner, repo
+                ),
+            });
+        }
+        if !status.is_success() {
+            return Err(Error::GitHubApiError(format!(
+                "GitHub API returned {} while resolving '{}'",
+                status, reference
+            )));
+        }
+
+        let sha = response
+            .text()
+            .await
+            .map_err(|e| Error::GitHubApiError(format!("Failed to read response: {}", e)))?
+            .trim()
+            .to_string();
+
+        if !is_commit_sha(&sha) {
+            return Err(Error::GitHubApiError(format!(
+                "Unexpected commit SHA for '{}': {}",
+                reference, sha
+            )));
+        }
+
+        Ok(sha)
     }
 }
 
+/// Find the URL for a relation (`next`, `last`, ...) in a `Link` header
+fn link_url<'a>(header: &'a str, rel: &str) -> Option<&'a str> {
+    let wanted = format!("rel=\"{}\"", rel);
+    header.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|p| p.trim() == wanted)
+            .then(|| url.trim().trim_start_matches('<').trim_end_matches('>'))
+    })
+}
+
+/// Check that a string is a full 40-character hex commit SHA
+pub fn is_commit_sha(value: &str) -> bool {
+    value.len() == 40 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 impl Default for GitHubDiscovery {
     fn default() -> Self {
         Self::new()
@@ -162,6 +476,7 @@ mod tests {
             stars: 0,
             language: "Rust".to_string(),
             topics: vec![],
+            default_branch: None,
         };
 
         assert_eq!(template.shorthand(), "user/repo");
@@ -178,10 +493,235 @@ mod tests {
             stars: 0,
             language: "Rust".to_string(),
             topics: vec!["axum".to_string(), "database".to_string()],
+            default_branch: Some("main".to_string()),
         };
 
         assert!(template.matches_tags(&[]));
         assert!(template.matches_tags(&["axum".to_string()]));
         assert!(!template.matches_tags(&["mongodb".to_string()]));
     }
+
+    #[test]
+    fn test_is_commit_sha() {
+        assert!(is_commit_sha("0123456789abcdef0123456789abcdef01234567"));
+        assert!(!is_commit_sha("main"));
+        assert!(!is_commit_sha("0123456789abcdef0123456789abcdef0123456z"));
+    }
+
+    const REPO_JSON: &str = r#"{
+        "name": "basic-api",
+        "description": "Basic x402 API",
+        "html_url": "https://github.com/x402/basic-api",
+        "owner": { "login": "x402" },
+        "stargazers_count": 42,
+        "language": "Rust",
+        "topics": ["x402-template", "axum"],
+        "default_branch": "trunk"
+    }"#;
+
+    #[tokio::test]
+    async fn test_discover_against_mock_server() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/search/repositories")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".to_string(),
+                "topic:x402-template".to_string(),
+            ))
+            .with_header("content-type", "application/json")
+            .with_body(format!(r#"{{ "total_count": 1, "items": [{}] }}"#, REPO_JSON))
+            .create_async()
+            .await;
+
+        let templates = GitHubDiscovery::with_base_url(&server.url())
+            .discover()
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].shorthand(), "x402/basic-api");
+        assert_eq!(templates[0].name, "Basic x402 API");
+        assert_eq!(templates[0].default_branch.as_deref(), Some("trunk"));
+    }
+
+    #[tokio::test]
+    async fn test_get_template_and_resolve_commit() {
+        let mut server = mockito::Server::new_async().await;
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        server
+            .mock("GET", "/repos/x402/basic-api")
+            .with_body(REPO_JSON)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/x402/basic-api/commits/v1.0.0")
+            .match_header("accept", "application/vnd.github.sha")
+            .with_body(sha)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/x402/basic-api/commits/nope")
+            .with_status(422)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/x402/missing")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let discovery = GitHubDiscovery::with_base_url(&server.url());
+
+        let template = discovery.get_template("x402", "basic-api").await.unwrap();
+        assert_eq!(template.stars, 42);
+        assert_eq!(
+            discovery.resolve_commit("x402", "basic-api", "v1.0.0").await.unwrap(),
+            sha
+        );
+        assert!(matches!(
+            discovery.resolve_commit("x402", "basic-api", "nope").await,
+            Err(Error::ValidationError { .. })
+        ));
+        assert!(matches!(
+            discovery.get_template("x402", "missing").await,
+            Err(Error::TemplateNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_file() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/x402/basic-api/contents/x402.toml")
+            .match_query(mockito::Matcher::UrlEncoded("ref".into(), "v1.0.0".into()))
+            .match_header("accept", "application/vnd.github.raw")
+            .with_body("[template]\n")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/x402/basic-api/contents/x402.toml")
+            .match_query(mockito::Matcher::Missing)
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let discovery = GitHubDiscovery::with_base_url(&server.url());
+
+        let content = discovery
+            .get_file("x402", "basic-api", "x402.toml", Some("v1.0.0"))
+            .await
+            .unwrap();
+        assert_eq!(content, "[template]\n");
+        assert!(matches!(
+            discovery.get_file("x402", "basic-api", "x402.toml", None).await,
+            Err(Error::TemplateNotFound(name)) if name.contains("no x402.toml")
+        ));
+    }
+
+    fn repo_json(owner: &str) -> String {
+        REPO_JSON.replace(r#""login": "x402""#, &format!(r#""login": "{}""#, owner))
+    }
+
+    fn search_page(server: &mut mockito::Server, page: usize, total: usize, owners: &[&str]) {
+        let items: Vec<String> = owners.iter().map(|o| repo_json(o)).collect();
+        let next = format!(
+            r#"<{url}?page={}>; rel="next", <{url}?page=3>; rel="last""#,
+            page + 1,
+            url = format!("{}/search/repositories", server.url())
+        );
+        let mut mock = server
+            .mock("GET", "/search/repositories")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "page".to_string(),
+                page.to_string(),
+            ))
+            .with_body(format!(
+                r#"{{ "total_count": {}, "items": [{}] }}"#,
+                total,
+                items.join(",")
+            ));
+        if page < 3 {
+            mock = mock.with_header("link", &next);
+        }
+        mock.create();
+    }
+
+    #[tokio::test]
+    async fn test_search_follows_pagination_and_dedupes() {
+        let mut server = mockito::Server::new_async().await;
+        search_page(&mut server, 1, 250, &["a", "b"]);
+        search_page(&mut server, 2, 250, &["b", "c"]);
+        search_page(&mut server, 3, 250, &["d"]);
+
+        let results = GitHubDiscovery::with_base_url(&server.url())
+            .search()
+            .await
+            .unwrap();
+
+        let owners: Vec<&str> = results.templates.iter().map(|t| t.owner.as_str()).collect();
+        assert_eq!(owners, ["a", "b", "c", "d"]);
+        assert_eq!(results.total_count, 250);
+        assert!(!results.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_search_stops_at_cap() {
+        let mut server = mockito::Server::new_async().await;
+        search_page(&mut server, 1, 250, &["a", "b", "c"]);
+
+        let results = GitHubDiscovery::with_base_url(&server.url())
+            .with_max_results(2)
+            .search()
+            .await
+            .unwrap();
+
+        assert_eq!(results.templates.len(), 2);
+        assert!(results.truncated);
+    }
+
+    #[test]
+    fn test_link_url() {
+        let header = concat!(
+            r#"<https://api.github.com/search?page=2>; rel="next", "#,
+            r#"<https://api.github.com/search?page=5>; rel="last""#
+        );
+        assert_eq!(link_url(header, "next"), Some("https://api.github.com/search?page=2"));
+        assert_eq!(link_url(header, "last"), Some("https://api.github.com/search?page=5"));
+        assert_eq!(link_url(header, "prev"), None);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_sends_validators_and_keeps_entry_on_304() {
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/search/repositories")
+            .match_query(mockito::Matcher::Any)
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", r#"W/"abc""#)
+            .with_header("last-modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+            .with_body(format!(r#"{{ "total_count": 1, "items": [{}] }}"#, REPO_JSON))
+            .create_async()
+            .await;
+        let conditional = server
+            .mock("GET", "/search/repositories")
+            .match_query(mockito::Matcher::Any)
+            .match_header("if-none-match", r#"W/"abc""#)
+            .match_header("if-modified-since", "Wed, 01 Jan 2025 00:00:00 GMT")
+            .with_status(304)
+            .create_async()
+            .await;
+
+        let discovery = GitHubDiscovery::with_base_url(&server.url());
+        let mut cached = discovery.refresh(None).await.unwrap();
+        first.assert_async().await;
+        assert_eq!(cached.etag.as_deref(), Some(r#"W/"abc""#));
+
+        cached.last_updated = chrono::Utc::now() - chrono::Duration::days(2);
+        let refreshed = discovery.refresh(Some(cached.clone())).await.unwrap();
+
+        conditional.assert_async().await;
+        assert_eq!(refreshed.templates, cached.templates);
+        assert!(refreshed.is_fresh(1));
+    }
 }
@@ -0,0 +1,269 @@
+//! GitHub and GitHub Enterprise hosts, with token authentication
+
+use crate::config::{Config, DEFAULT_HOST};
+use std::process::Command;
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Environment variable overriding the default host's API base URL, e.g. to
+/// point cargo-x402 at a mirror or a local mock server
+pub const API_URL_ENV: &str = "X402_GITHUB_API_URL";
+
+/// Environment variables checked for a github.com token, in order
+const GITHUB_TOKEN_VARS: &[&str] = &["GITHUB_TOKEN", "GH_TOKEN"];
+
+/// Environment variables checked for a GitHub Enterprise token, in order
+/// (the same ones the `gh` CLI uses)
+const ENTERPRISE_TOKEN_VARS: &[&str] = &["GITHUB_ENTERPRISE_TOKEN", "GH_ENTERPRISE_TOKEN"];
+
+/// A GitHub host with its API and web base URLs and an optional token
+#[derive(Clone, PartialEq, Eq)]
+pub struct GitHubHost {
+    /// Host name, e.g. `github.com` or `github.example.com`
+    pub name: String,
+
+    /// REST API base URL, without a trailing slash
+    pub api_url: String,
+
+    /// Web base URL, without a trailing slash
+    pub web_url: String,
+
+    /// Access token sent as a bearer token on API requests
+    pub token: Option<String>,
+}
+
+impl GitHubHost {
+    /// Anonymous access to public github.com
+    pub fn public() -> Self {
+        Self {
+            name: DEFAULT_HOST.to_string(),
+            api_url: GITHUB_API_URL.to_string(),
+            web_url: format!("https://{}", DEFAULT_HOST),
+            token: None,
+        }
+    }
+
+    /// Anonymous access to github.com through a different API base URL
+    #[allow(dead_code)] // Intentionally public for library users
+    pub fn with_api_url(api_url: &str) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            ..Self::public()
+        }
+    }
+
+    /// A host's API and web URLs from the config file, without credentials
+    ///
+    /// For the default host, `X402_GITHUB_API_URL` takes precedence over the
+    /// configured API URL.
+    pub fn from_config(name: &str, config: &Config) -> Self {
+        let host_config = config.host(name).cloned().unwrap_or_default();
+
+        let env_api_url = std::env::var(API_URL_ENV)
+            .ok()
+            .filter(|url| !url.is_empty() && name == config.default_host());
+        let api_url = env_api_url
+            .or(host_config.api_url)
+            .unwrap_or_else(|| Self::default_api_url(name));
+        let web_url = host_config
+            .web_url
+            .unwrap_or_else(|| format!("https://{}", name));
+
+        Self {
+            name: name.to_string(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            web_url: web_url.trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    /// Resolve a host's URLs and token from the config file, the
+    /// environment and the `gh` CLI
+    ///
+    /// Tokens are looked up in order: `GITHUB_TOKEN`/`GH_TOKEN` (or
+    /// `GITHUB_ENTERPRISE_TOKEN`/`GH_ENTERPRISE_TOKEN` for other hosts), the
+    /// host's `token` in the config file, then `gh auth token`.
+    pub fn resolve(name: &str, config: &Config) -> Self {
+        let config_token = config.host(name).and_then(|h| h.token.clone());
+        let token = Self::env_token(name)
+            .or(config_token)
+            .or_else(|| Self::gh_token(name));
+
+        Self {
+            token,
+            ..Self::from_config(name, config)
+        }
+    }
+
+    /// The configured default host, with its token
+    pub fn default_from(config: &Config) -> Self {
+        Self::resolve(config.default_host(), config)
+    }
+
+    /// Every host a template URL may refer to: github.com, the default host
+    /// and all configured hosts
+    pub fn known_hosts(config: &Config) -> Vec<String> {
+        let mut hosts = vec![DEFAULT_HOST.to_string(), config.default_host().to_string()];
+        hosts.extend(config.hosts.keys().cloned());
+        hosts.sort();
+        hosts.dedup();
+        hosts
+    }
+
+    fn default_api_url(name: &str) -> String {
+        if name == DEFAULT_HOST {
+            GITHUB_API_URL.to_string()
+        } else {
+            format!("https://{}/api/v3", name)
+        }
+    }
+
+    fn token_vars(name: &str) -> &'static [&'static str] {
+        if name == DEFAULT_HOST {
+            GITHUB_TOKEN_VARS
+        } else {
+            ENTERPRISE_TOKEN_VARS
+        }
+    }
+
+    fn env_token(name: &str) -> Option<String> {
+        Self::token_vars(name)
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .map(|token| token.trim().to_string())
+            .find(|token| !token.is_empty())
+    }
+
+    /// Ask the `gh` CLI for its stored token, if it is installed and logged in
+    fn gh_token(name: &str) -> Option<String> {
+        let output = Command::new("gh")
+            .args(["auth", "token", "--hostname", name])
+            .env("GH_PROMPT_DISABLED", "1")
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+        (!token.is_empty()).then_some(token)
+    }
+
+    /// How to provide a token for this host, for error messages
+    pub fn missing_token_hint(&self) -> String {
+        format!(
+            "private repositories need {} or `gh auth login --hostname {}`",
+            Self::token_vars(&self.name).join("/"),
+            self.name
+        )
+    }
+
+    /// Split a repository web URL on this host into owner and repo
+    pub fn parse_repo_url(&self, url: &str) -> Option<(String, String)> {
+        let path = url.strip_prefix(&self.web_url)?.strip_prefix('/')?;
+        let mut parts = path.trim_end_matches('/').split('/');
+        let owner = parts.next().filter(|p| !p.is_empty())?;
+        let repo = parts.next().filter(|p| !p.is_empty())?;
+        Some((owner.to_string(), repo.trim_end_matches(".git").to_string()))
+    }
+
+    /// Add the `Authorization` header when a token is available
+    pub fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+impl Default for GitHubHost {
+    fn default() -> Self {
+        Self::public()
+    }
+}
+
+// Hand-written so tokens never end up in logs
+impl std::fmt::Debug for GitHubHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubHost")
+            .field("name", &self.name)
+            .field("api_url", &self.api_url)
+            .field("web_url", &self.web_url)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HostConfig;
+
+    fn enterprise_config() -> Config {
+        let mut config = Config {
+            default_host: Some("github.example.com".to_string()),
+            ..Config::default()
+        };
+        config.hosts.insert(
+            "github.example.com".to_string(),
+            HostConfig {
+                web_url: Some("https://github.example.com/".to_string()),
+                ..HostConfig::default()
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn test_resolve_urls() {
+        let config = enterprise_config();
+
+        let host = GitHubHost::from_config(config.default_host(), &config);
+        assert_eq!(host.name, "github.example.com");
+        assert_eq!(host.api_url, "https://github.example.com/api/v3");
+        assert_eq!(host.web_url, "https://github.example.com");
+
+        let public = GitHubHost::from_config("github.com", &config);
+        assert_eq!(public.api_url, "https://api.github.com");
+        assert_eq!(public.web_url, "https://github.com");
+    }
+
+    #[test]
+    fn test_token_vars_per_host() {
+        assert_eq!(GitHubHost::token_vars("github.com"), GITHUB_TOKEN_VARS);
+        assert_eq!(GitHubHost::token_vars("github.example.com"), ENTERPRISE_TOKEN_VARS);
+    }
+
+    #[test]
+    fn test_parse_repo_url() {
+        let host = GitHubHost::public();
+        assert_eq!(
+            host.parse_repo_url("https://github.com/user/repo.git"),
+            Some(("user".to_string(), "repo".to_string()))
+        );
+        assert_eq!(
+            host.parse_repo_url("https://github.com/user/repo/tree/main"),
+            Some(("user".to_string(), "repo".to_string()))
+        );
+        assert_eq!(host.parse_repo_url("https://github.com/user"), None);
+        assert_eq!(host.parse_repo_url("https://github.company.com/user/repo"), None);
+    }
+
+    #[test]
+    fn test_known_hosts() {
+        assert_eq!(
+            GitHubHost::known_hosts(&enterprise_config()),
+            vec!["github.com".to_string(), "github.example.com".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_debug_redacts_token() {
+        let host = GitHubHost {
+            token: Some("ghp_secret".to_string()),
+            ..GitHubHost::public()
+        };
+        assert!(!format!("{:?}", host).contains("ghp_secret"));
+    }
+}
@@ -0,0 +1,256 @@
+//! Sending GitHub requests with retries and rate-limit detection
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::time::Duration;
+
+/// Attempts per request, including the first
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled for each further retry
+const BASE_DELAY: Duration = Duration::from_millis(if cfg!(test) { 1 } else { 500 });
+
+/// Longest `Retry-After` we are willing to sleep through instead of failing
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(10);
+
+/// Send a request, retrying network failures and 5xx responses with
+/// exponential backoff
+///
+/// Rate-limited responses become [`Error::RateLimited`], unless GitHub asks
+/// us to wait only briefly. Any other response is returned for the caller
+/// to inspect.
+pub async fn send(request: RequestBuilder) -> Result<Response> {
+    send_attempts(request, None).await
+}
+
+/// Like [`send`], but give each attempt at most `timeout` to return
+/// response headers
+///
+/// An attempt that times out is retried like a network failure. The
+/// backoff between attempts does not count towards the timeout.
+pub async fn send_with_timeout(request: RequestBuilder, timeout: Duration) -> Result<Response> {
+    send_attempts(request, Some(timeout)).await
+}
+
+async fn send_attempts(request: RequestBuilder, timeout: Option<Duration>) -> Result<Response> {
+    let mut attempt = 1;
+    loop {
+        // Requests without a streaming body can always be cloned
+        let this_try = request
+            .try_clone()
+            .ok_or_else(|| Error::Other("Request cannot be retried".to_string()))?;
+        let last_attempt = attempt == MAX_ATTEMPTS;
+
+        let sent = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, this_try.send()).await.ok(),
+            None => Some(this_try.send().await),
+        };
+
+        match sent {
+            None if !last_attempt => {
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+            None => {
+                return Err(Error::NetworkError(format!(
+                    "No response received within {}s",
+                    timeout.unwrap_or_default().as_secs_f64()
+                )))
+            }
+            Some(Ok(response)) => {
+                if let Some(limit) = RateLimit::from_response(&response) {
+                    match limit.retry_after {
+                        Some(wait) if wait <= MAX_RETRY_AFTER && !last_attempt => {
+                            tokio::time::sleep(wait).await;
+                        }
+                        _ => return Err(Error::RateLimited { reset_at: limit.reset_at }),
+                    }
+                } else if response.status().is_server_error() && !last_attempt {
+                    tokio::time::sleep(backoff(attempt)).await;
+                } else {
+                    return Ok(response);
+                }
+            }
+            Some(Err(e)) if is_transient(&e) && !last_attempt => {
+                tokio::time::sleep(backoff(attempt)).await;
+            }
+            Some(Err(e)) => return Err(Error::NetworkError(format!("Request failed: {}", e))),
+        }
+
+        attempt += 1;
+    }
+}
+
+fn backoff(attempt: u32) -> Duration {
+    BASE_DELAY * 2u32.pow(attempt - 1)
+}
+
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect()
+}
+
+/// What a throttled response told us about when to come back
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RateLimit {
+    /// When the limit resets, if GitHub said
+    reset_at: Option<DateTime<Utc>>,
+
+    /// How long GitHub asked us to wait, from `Retry-After`
+    retry_after: Option<Duration>,
+}
+
+impl RateLimit {
+    fn from_response(response: &Response) -> Option<Self> {
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Self::from_parts(
+            response.status(),
+            header("x-ratelimit-remaining").as_deref(),
+            header("x-ratelimit-reset").as_deref(),
+            header("retry-after").as_deref(),
+            Utc::now(),
+        )
+    }
+
+    /// Decide whether a response is throttled
+    ///
+    /// GitHub signals the primary limit with `403`/`429` and
+    /// `X-RateLimit-Remaining: 0`, and secondary limits with `Retry-After`.
+    fn from_parts(
+        status: StatusCode,
+        remaining: Option<&str>,
+        reset: Option<&str>,
+        retry_after: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Option<Self> {
+        let retry_after = retry_after
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let exhausted = remaining.map(str::trim) == Some("0");
+
+        let throttled = match status {
+            StatusCode::TOO_MANY_REQUESTS => true,
+            StatusCode::FORBIDDEN => exhausted || retry_after.is_some(),
+            _ => false,
+        };
+        if !throttled {
+            return None;
+        }
+
+        let reset_at = reset
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .or_else(|| {
+                retry_after.and_then(|wait| {
+                    chrono::Duration::from_std(wait).ok().map(|wait| now + wait)
+                })
+            });
+
+        Some(Self {
+            reset_at,
+            retry_after,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primary_rate_limit() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let limit = RateLimit::from_parts(
+            StatusCode::FORBIDDEN,
+            Some("0"),
+            Some("1700000600"),
+            None,
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(limit.reset_at, Utc.timestamp_opt(1_700_000_600, 0).single());
+        assert_eq!(limit.retry_after, None);
+    }
+
+    #[test]
+    fn test_secondary_rate_limit() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let limit =
+            RateLimit::from_parts(StatusCode::TOO_MANY_REQUESTS, None, None, Some("30"), now)
+                .unwrap();
+
+        assert_eq!(limit.reset_at, Some(now + chrono::Duration::seconds(30)));
+        assert_eq!(limit.retry_after, Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_forbidden_is_not_always_throttled() {
+        let now = Utc::now();
+        assert!(RateLimit::from_parts(StatusCode::FORBIDDEN, Some("42"), None, None, now)
+            .is_none());
+        assert!(RateLimit::from_parts(StatusCode::OK, Some("0"), None, None, now).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let failing = server
+            .mock("GET", "/flaky")
+            .with_status(502)
+            .expect(MAX_ATTEMPTS as usize)
+            .create_async()
+            .await;
+
+        let client = reqwest::Client::new();
+        let response = send(client.get(format!("{}/flaky", server.url())))
+            .await
+            .unwrap();
+
+        failing.assert_async().await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn test_times_out_each_attempt() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/slow", listener.local_addr().unwrap());
+
+        // Accepts connections but never answers
+        let client = reqwest::Client::new();
+        let started = std::time::Instant::now();
+        let result = send_with_timeout(client.get(url), Duration::from_millis(50)).await;
+
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("No response received"), "{}", message);
+        assert!(started.elapsed() >= Duration::from_millis(50) * MAX_ATTEMPTS);
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_response() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/search")
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", "1700000600")
+            .with_body(r#"{"message": "API rate limit exceeded"}"#)
+            .create_async()
+            .await;
+
+        let client = reqwest::Client::new();
+        let result = send(client.get(format!("{}/search", server.url()))).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::RateLimited { reset_at: Some(_) })
+        ));
+    }
+}
@@ -8,12 +8,15 @@
 //!
 //! - [`github`]: GitHub API integration for template discovery
 //! - [`cache`]: Local caching of discovered templates
+//! - [`host`]: GitHub and GitHub Enterprise hosts and token lookup
+//! - [`http`]: Retries, backoff and rate-limit detection for GitHub requests
 //!
 //! ## Overview
 //!
 //! The discovery process:
 //! 1. Check local cache for recently discovered templates
-//! 2. If cache is fresh (< 1 hour old), return cached results
+//! 2. If cache is fresh (younger than the configured TTL, 1 hour by default),
+//!    return cached results
 //! 3. If cache is stale or missing, query GitHub API
 //! 4. Update cache with new results
 //! 5. Return template list to caller
@@ -31,9 +34,12 @@
 
 pub mod cache;
 pub mod github;
+pub mod host;
+pub mod http;
 
 pub use github::GitHubDiscovery;
 pub use cache::Cache;
+pub use host::GitHubHost;
 
 use serde::{Deserialize, Serialize};
 
@@ -67,6 +73,10 @@ pub struct TemplateInfo {
     /// GitHub topics
     #[serde(default)]
     pub topics: Vec<String>,
+
+    /// Default branch of the repository, when known
+    #[serde(default)]
+    pub default_branch: Option<String>,
 }
 
 impl TemplateInfo {
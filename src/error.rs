@@ -33,8 +33,10 @@
 //! - **ParameterError**: User parameter validation or processing failed
 //! - **RenderError**: Liquid template rendering failed
 //! - **GitHubApiError**: GitHub API request failed
+//! - **RateLimited**: GitHub API rate limit exhausted
 //! - **TomlError**: TOML/JSON parsing failed
 //! - **CacheError**: Cache directory operation failed
+//! - **UnsafeArchive**: Downloaded archive refused during extraction ([`ArchiveViolation`])
 //! - **Cancelled**: User cancelled operation (e.g., interactive prompt)
 //! - **Other**: Generic error for miscellaneous cases
 //!
@@ -44,6 +46,8 @@
 //! - `Display`: User-friendly message with helpful guidance
 //! - `Debug`: Detailed error information for troubleshooting
 
+use chrono::{DateTime, Local, Utc};
+use indicatif::HumanBytes;
 use std::fmt;
 
 /// Custom error type for cargo-x402
@@ -55,6 +59,7 @@ use std::fmt;
 /// for users to understand what went wrong and how to fix it.
 #[derive(Debug)]
 #[allow(dead_code)] // Variants are part of public API for future use
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     /// Template not found in discovery results
     TemplateNotFound(String),
@@ -85,12 +90,21 @@ pub enum Error {
     /// GitHub API error
     GitHubApiError(String),
 
+    /// GitHub API rate limit exhausted
+    RateLimited {
+        /// When the limit resets, if GitHub said
+        reset_at: Option<DateTime<Utc>>,
+    },
+
     /// TOML parsing error
     TomlError(String),
 
     /// Cache operation error
     CacheError(String),
 
+    /// Archive refused during extraction
+    UnsafeArchive(ArchiveViolation),
+
     /// User cancelled operation
     Cancelled,
 
@@ -125,12 +139,28 @@ impl fmt::Display for Error {
             Error::GitHubApiError(msg) => {
                 write!(f, "GitHub API error: {}\n\nCheck your internet connection or rate limits", msg)
             }
+            Error::RateLimited { reset_at } => {
+                write!(f, "GitHub API rate limit exceeded")?;
+                if let Some(reset_at) = reset_at {
+                    let minutes = (*reset_at - Utc::now()).num_minutes().max(0) + 1;
+                    write!(
+                        f,
+                        "; it resets at {} (in about {} min)",
+                        reset_at.with_timezone(&Local).format("%H:%M"),
+                        minutes
+                    )?;
+                }
+                write!(f, "\n\nSet GITHUB_TOKEN or run `gh auth login` for a higher limit")
+            }
             Error::TomlError(msg) => {
                 write!(f, "TOML parsing error: {}", msg)
             }
             Error::CacheError(msg) => {
                 write!(f, "Cache error: {}", msg)
             }
+            Error::UnsafeArchive(violation) => {
+                write!(f, "Refusing to extract archive: {}", violation)
+            }
             Error::Cancelled => {
                 write!(f, "Operation cancelled by user")
             }
@@ -143,6 +173,71 @@ impl fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
+/// Why an archive was refused during extraction
+///
+/// Archives come from the network, so every entry is checked before it is
+/// written; see `Downloader::with_limits` for the configurable caps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveViolation {
+    /// Entry path is absolute or climbs out with `..`
+    UnsafePath {
+        /// Entry name as stored in the archive
+        entry: String,
+    },
+
+    /// Symbolic or hard link pointing outside the archive
+    UnsafeLink {
+        /// Entry name as stored in the archive
+        entry: String,
+        /// Where the link points
+        target: String,
+    },
+
+    /// More entries than allowed
+    TooManyEntries {
+        /// The entry limit
+        limit: usize,
+    },
+
+    /// Extracted files add up to more than allowed
+    TooLarge {
+        /// The size limit in bytes
+        limit: u64,
+    },
+
+    /// Extracted files are suspiciously large for the archive's size
+    CompressionRatio {
+        /// The largest allowed ratio of extracted to archive bytes
+        limit: u64,
+    },
+}
+
+impl fmt::Display for ArchiveViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveViolation::UnsafePath { entry } => {
+                write!(f, "entry '{}' would be written outside the destination", entry)
+            }
+            ArchiveViolation::UnsafeLink { entry, target } => {
+                write!(f, "link '{}' points outside the archive ({})", entry, target)
+            }
+            ArchiveViolation::TooManyEntries { limit } => {
+                write!(f, "it has more than {} entries", limit)
+            }
+            ArchiveViolation::TooLarge { limit } => {
+                write!(f, "it extracts to more than {}", HumanBytes(*limit))
+            }
+            ArchiveViolation::CompressionRatio { limit } => {
+                write!(
+                    f,
+                    "it expands more than {} times its size, which looks like a zip bomb",
+                    limit
+                )
+            }
+        }
+    }
+}
+
 // Conversion implementations
 
 impl From<serde_json::Error> for Error {
@@ -268,6 +363,20 @@ mod tests {
         assert!(msg.contains("rate limits"));
     }
 
+    #[test]
+    fn test_error_rate_limited() {
+        let err = Error::RateLimited {
+            reset_at: Some(Utc::now() + chrono::Duration::minutes(10)),
+        };
+        let msg = err.to_string();
+        assert!(msg.contains("rate limit exceeded"));
+        assert!(msg.contains("resets at"));
+        assert!(msg.contains("GITHUB_TOKEN"));
+
+        let msg = Error::RateLimited { reset_at: None }.to_string();
+        assert!(!msg.contains("resets at"));
+    }
+
     #[test]
     fn test_error_toml_error() {
         let err = Error::TomlError("invalid syntax".to_string());
@@ -284,6 +393,19 @@ mod tests {
         assert!(msg.contains("cache directory not writable"));
     }
 
+    #[test]
+    fn test_error_unsafe_archive() {
+        let err = Error::UnsafeArchive(ArchiveViolation::UnsafePath {
+            entry: "../evil.sh".to_string(),
+        });
+        let msg = err.to_string();
+        assert!(msg.contains("Refusing to extract archive"));
+        assert!(msg.contains("'../evil.sh' would be written outside"));
+
+        let err = Error::UnsafeArchive(ArchiveViolation::TooLarge { limit: 1024 });
+        assert!(err.to_string().contains("more than 1.00 KiB"));
+    }
+
     #[test]
     fn test_error_cancelled() {
         let err = Error::Cancelled;
@@ -343,6 +465,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::invalid_regex)] // The invalid pattern is the point of the test
     fn test_error_from_regex_error() {
         let regex_err = regex::Regex::new("[invalid").err().unwrap();
         let err = Error::from(regex_err);
@@ -356,6 +479,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_literal_unwrap)]
     fn test_result_type_ok() {
         let result: Result<i32> = Ok(42);
         assert_eq!(result.unwrap(), 42);
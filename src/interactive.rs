@@ -28,9 +28,9 @@
 
 use crate::discovery::TemplateInfo;
 use crate::error::{Error, Result};
-use crate::schema::Parameter;
+use crate::schema::{Parameter, ParameterValue, ParameterValues};
 use colored::*;
-use dialoguer::{theme::ColorfulTheme, Input, Select};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
 use std::collections::HashMap;
 
 /// Select a template from a list interactively.
@@ -83,8 +83,9 @@ pub fn prompt_project_name(default: Option<&str>) -> Result<String> {
 }
 
 /// Prompt for parameter values based on template parameters
-pub fn prompt_for_parameters(parameters: &HashMap<String, Parameter>) -> Result<HashMap<String, String>> {
-    let mut values = HashMap::new();
+#[allow(dead_code)] // Intentionally public for library users
+pub fn prompt_for_parameters(parameters: &HashMap<String, Parameter>) -> Result<ParameterValues> {
+    let mut values = ParameterValues::new();
 
     for (name, param) in parameters {
         let value = prompt_for_parameter(name, param)?;
@@ -95,7 +96,7 @@ pub fn prompt_for_parameters(parameters: &HashMap<String, Parameter>) -> Result<
 }
 
 /// Prompt for a single parameter value
-fn prompt_for_parameter(name: &str, param: &Parameter) -> Result<String> {
+pub fn prompt_for_parameter(name: &str, param: &Parameter) -> Result<ParameterValue> {
     let theme = ColorfulTheme::default();
 
     match param {
@@ -124,7 +125,10 @@ fn prompt_for_parameter(name: &str, param: &Parameter) -> Result<String> {
                 });
             }
 
-            input.interact_text().map_err(|_| Error::Cancelled)
+            input
+                .interact_text()
+                .map(ParameterValue::String)
+                .map_err(|_| Error::Cancelled)
         }
 
         Parameter::Boolean { default, description } => {
@@ -137,7 +141,7 @@ fn prompt_for_parameter(name: &str, param: &Parameter) -> Result<String> {
                 .interact()
                 .map_err(|_| Error::Cancelled)?;
 
-            Ok((selection == 0).to_string())
+            Ok(ParameterValue::Boolean(selection == 0))
         }
 
         Parameter::Enum {
@@ -157,11 +161,50 @@ fn prompt_for_parameter(name: &str, param: &Parameter) -> Result<String> {
                 .interact()
                 .map_err(|_| Error::Cancelled)?;
 
-            Ok(choices[selection].clone())
+            Ok(ParameterValue::String(choices[selection].clone()))
         }
     }
 }
 
+/// Prompt for free text, asking again until `check` accepts the answer.
+///
+/// Without a default an empty answer is allowed (and passed to `check`).
+pub fn prompt_text<F>(prompt: &str, default: Option<&str>, check: F) -> Result<String>
+where
+    F: FnMut(&String) -> std::result::Result<(), String>,
+{
+    let theme = ColorfulTheme::default();
+    let mut input = Input::with_theme(&theme)
+        .with_prompt(prompt)
+        .validate_with(check);
+
+    match default {
+        Some(default) => input = input.default(default.to_string()),
+        None => input = input.allow_empty(true),
+    }
+
+    input.interact_text().map_err(|_| Error::Cancelled)
+}
+
+/// Ask a yes/no question
+pub fn prompt_confirm(prompt: &str, default: bool) -> Result<bool> {
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(default)
+        .interact()
+        .map_err(|_| Error::Cancelled)
+}
+
+/// Pick one of `items`, returning its index
+pub fn prompt_choice(prompt: &str, items: &[&str], default: usize) -> Result<usize> {
+    Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(default)
+        .items(items)
+        .interact()
+        .map_err(|_| Error::Cancelled)
+}
+
 /// Format prompt text with description
 fn format_prompt(name: &str, description: Option<&str>) -> String {
     let formatted_name = name.replace('_', " ").to_title_case();
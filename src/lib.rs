@@ -21,6 +21,7 @@
 //! - [`template`]: Downloading and rendering templates
 //! - [`interactive`]: User interaction and prompts
 //! - [`commands`]: High-level operations (list, create)
+//! - [`config`]: User configuration file (hosts and tokens)
 //! - [`error`]: Error types and handling
 //!
 //! ## Quick Example
@@ -33,6 +34,7 @@
 //! ```
 
 pub mod commands;
+pub mod config;
 pub mod discovery;
 pub mod error;
 pub mod interactive;
@@ -1,8 +1,16 @@
 use clap::{Parser, Subcommand};
 use colored::*;
+use commands::create::CreateOptions;
+use commands::info::InfoOptions;
+use commands::list::ListOptions;
+use commands::template::extract::ExtractOptions;
+use commands::template::init::InitOptions;
+use commands::test::TestOptions;
+use std::path::PathBuf;
 use std::process;
 
 mod commands;
+mod config;
 mod discovery;
 mod error;
 mod interactive;
@@ -19,6 +27,19 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
     author = "x402 Community"
 )]
 struct Cli {
+    /// Work from cached template lists and archives only, without network access
+    #[arg(
+        long,
+        global = true,
+        env = "X402_OFFLINE",
+        value_parser = clap::builder::FalseyValueParser::new()
+    )]
+    offline: bool,
+
+    /// Hours before the cached template list is refreshed (default: from the config file, or 1)
+    #[arg(long, global = true, value_name = "HOURS")]
+    cache_ttl: Option<u64>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -40,13 +61,110 @@ enum Commands {
     /// Create a new x402 project from a template
     #[command(visible_alias = "new")]
     Create {
-        /// Template GitHub URL or shorthand (e.g., user/repo or https://github.com/user/repo)
+        /// Template GitHub URL or shorthand (e.g., user/repo, user/repo@v1.2.0 or a full URL)
         #[arg(short, long)]
         template: Option<String>,
 
+        /// Branch, tag or commit SHA of the template (default: the repo's default branch)
+        #[arg(long = "ref", value_name = "REF")]
+        git_ref: Option<String>,
+
         /// Project name
         #[arg(short, long)]
         name: Option<String>,
+
+        /// Set a template parameter (repeatable), e.g. --define enable_docker=false
+        #[arg(short = 'D', long = "define", value_name = "NAME=VALUE")]
+        define: Vec<String>,
+
+        /// Read parameter values from a TOML or JSON file
+        #[arg(long, value_name = "FILE")]
+        values_file: Option<PathBuf>,
+
+        /// Use default values for every parameter without prompting
+        #[arg(short = 'y', long, visible_alias = "yes")]
+        defaults: bool,
+
+        /// Show the files that would be created, with sizes, without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// With --dry-run, show how rendering changes the files matching GLOB (default: all)
+        #[arg(
+            long,
+            value_name = "GLOB",
+            requires = "dry_run",
+            num_args = 0..=1,
+            default_missing_value = "*"
+        )]
+        diff: Vec<String>,
+    },
+
+    /// Show a template's parameters and metadata without creating a project
+    #[command(visible_alias = "show")]
+    Info {
+        /// Template shorthand (user/repo or user/repo@ref), GitHub URL, name or local directory
+        template: String,
+
+        /// Branch, tag or commit SHA of the template (default: the repo's default branch)
+        #[arg(long = "ref", value_name = "REF")]
+        git_ref: Option<String>,
+
+        /// Print the manifest as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check a local template for mistakes before publishing it
+    #[command(visible_alias = "lint")]
+    Validate {
+        /// Template directory (default: the current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Fail on warnings as well as errors
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Build a local template with every combination of its boolean and enum parameters
+    Test {
+        /// Template directory (default: the current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Command run in each rendered project
+        #[arg(long, value_name = "COMMAND", default_value = commands::test::DEFAULT_CHECK)]
+        check: String,
+
+        /// Run this many randomly chosen combinations instead of all of them
+        #[arg(long, value_name = "N", value_parser = at_least_one)]
+        sample: Option<usize>,
+
+        /// Sample larger matrices down to this many combinations
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = commands::test::DEFAULT_MAX_COMBINATIONS,
+            value_parser = at_least_one
+        )]
+        max_combinations: usize,
+
+        /// Seed for choosing sampled combinations, to repeat an earlier run
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+
+    /// Tools for template authors
+    Template {
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+
+    /// Show, prune or clear the local template cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
     },
 
     /// Show version information
@@ -54,13 +172,159 @@ enum Commands {
     Version,
 }
 
+#[derive(Subcommand)]
+enum TemplateAction {
+    /// Start a new template: x402.toml, sample Liquid files and README docs
+    Init {
+        /// Template directory, created if missing (default: the current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Write an example manifest without prompting
+        #[arg(short = 'y', long, visible_alias = "yes")]
+        defaults: bool,
+
+        /// Overwrite an existing x402.toml
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Turn a working project into a template, replacing chosen values with variables
+    Extract {
+        /// Project directory to convert
+        project: PathBuf,
+
+        /// Where to write the template (default: <project>-template next to the project)
+        #[arg(short, long, value_name = "DIR")]
+        output: Option<PathBuf>,
+
+        /// Also replace this value with a variable (repeatable), e.g. --var pay_to=0xabc...
+        #[arg(long = "var", value_name = "NAME=VALUE")]
+        vars: Vec<String>,
+
+        /// Replace every detected value without prompting
+        #[arg(short = 'y', long, visible_alias = "yes")]
+        defaults: bool,
+
+        /// Write into an existing, non-empty output directory
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Show the cached template list and archives with their ages and sizes
+    Info,
+
+    /// Delete the cached template list and all downloaded archives
+    Clear,
+
+    /// Delete archives that are no longer needed
+    Prune {
+        /// Also delete archives downloaded more than this many days ago
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<u64>,
+    },
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    let offline = cli.offline;
+    let cache_ttl = cli.cache_ttl;
 
     let result = match cli.command {
-        Some(Commands::List { refresh, tags }) => commands::list::execute(refresh, tags).await,
-        Some(Commands::Create { template, name }) => commands::create::execute(template, name).await,
+        Some(Commands::List { refresh, tags }) => {
+            commands::list::execute(ListOptions {
+                refresh,
+                tags,
+                offline,
+                cache_ttl,
+            })
+            .await
+        }
+        Some(Commands::Create {
+            template,
+            git_ref,
+            name,
+            define,
+            values_file,
+            defaults,
+            dry_run,
+            diff,
+        }) => {
+            commands::create::execute(CreateOptions {
+                template,
+                name,
+                defines: define,
+                values_file,
+                use_defaults: defaults,
+                reference: git_ref,
+                offline,
+                cache_ttl,
+                dry_run,
+                diff,
+            })
+            .await
+        }
+        Some(Commands::Info {
+            template,
+            git_ref,
+            json,
+        }) => {
+            commands::info::execute(InfoOptions {
+                template,
+                reference: git_ref,
+                json,
+                offline,
+                cache_ttl,
+            })
+            .await
+        }
+        Some(Commands::Validate { path, strict }) => commands::validate::execute(&path, strict),
+        Some(Commands::Test {
+            path,
+            check,
+            sample,
+            max_combinations,
+            seed,
+        }) => commands::test::execute(TestOptions {
+            path,
+            check,
+            sample,
+            max_combinations,
+            seed,
+        }),
+        Some(Commands::Template { action }) => match action {
+            TemplateAction::Init {
+                path,
+                defaults,
+                force,
+            } => commands::template::init::execute(InitOptions {
+                path,
+                use_defaults: defaults,
+                force,
+            }),
+            TemplateAction::Extract {
+                project,
+                output,
+                vars,
+                defaults,
+                force,
+            } => commands::template::extract::execute(ExtractOptions {
+                project,
+                output,
+                vars,
+                use_defaults: defaults,
+                force,
+            }),
+        },
+        Some(Commands::Cache { action }) => match action {
+            CacheAction::Info => commands::cache::info(cache_ttl),
+            CacheAction::Clear => commands::cache::clear(cache_ttl),
+            CacheAction::Prune { older_than } => commands::cache::prune(older_than),
+        },
         Some(Commands::Version) => {
             println!("cargo-x402 {}", VERSION);
             Ok(())
@@ -72,7 +336,12 @@ async fn main() {
                 "{}\n",
                 "Use 'cargo-x402 --help' to see all options".dimmed()
             );
-            commands::create::execute(None, None).await
+            commands::create::execute(CreateOptions {
+                offline,
+                cache_ttl,
+                ..CreateOptions::default()
+            })
+            .await
         }
     };
 
@@ -81,3 +350,12 @@ async fn main() {
         process::exit(1);
     }
 }
+
+/// Parse a count that must be at least 1
+fn at_least_one(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(count) => Ok(count),
+        Err(e) => Err(e.to_string()),
+    }
+}
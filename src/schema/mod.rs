@@ -41,5 +41,7 @@ pub mod template;
 pub mod validator;
 
 #[allow(unused_imports)] // Intentionally exported for library users
-pub use template::{Parameter, TemplateMetadata, TemplateSchema};
+pub use template::{
+    Parameter, ParameterValue, ParameterValues, TemplateMetadata, TemplateSchema,
+};
 pub use validator::Validator;
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 /// Complete x402 template schema from x402.toml.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +15,57 @@ pub struct TemplateSchema {
     pub files: Option<FileRules>,
 }
 
+impl TemplateSchema {
+    /// Serialize back to x402.toml, with parameters sorted by name and
+    /// written as inline tables, the way TEMPLATE_SCHEMA.md lays them out
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        #[derive(Serialize)]
+        struct Section<'a, T: Serialize> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            template: Option<&'a T>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            files: Option<&'a T>,
+        }
+
+        let mut out = toml::to_string(&Section {
+            template: Some(&self.template),
+            files: None,
+        })?;
+
+        if let Some(params) = self.parameters.as_ref().filter(|p| !p.is_empty()) {
+            let mut names: Vec<&String> = params.keys().collect();
+            names.sort();
+            out.push_str("\n[parameters]\n");
+            for name in names {
+                out.push_str(&format!("{} = {}\n", toml_key(name), params[name].to_inline_toml()));
+            }
+        }
+
+        if let Some(files) = &self.files {
+            out.push('\n');
+            out.push_str(&toml::to_string(&Section {
+                template: None,
+                files: Some(files),
+            })?);
+        }
+
+        Ok(out)
+    }
+}
+
+/// A TOML key, quoted unless it is a bare key
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
 /// Template metadata from `[template]` section of x402.toml.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TemplateMetadata {
@@ -86,7 +138,124 @@ pub enum Parameter {
     },
 }
 
+/// A parameter value typed according to its [`Parameter`] declaration.
+///
+/// Booleans render as real Liquid booleans so `{% if flag %}` is false when
+/// the user answered "No"; string and enum values render as strings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterValue {
+    /// Value of a boolean parameter
+    Boolean(bool),
+    /// Value of a string or enum parameter
+    String(String),
+}
+
+impl fmt::Display for ParameterValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterValue::Boolean(b) => write!(f, "{}", b),
+            ParameterValue::String(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<String> for ParameterValue {
+    fn from(value: String) -> Self {
+        ParameterValue::String(value)
+    }
+}
+
+impl From<&str> for ParameterValue {
+    fn from(value: &str) -> Self {
+        ParameterValue::String(value.to_string())
+    }
+}
+
+impl From<bool> for ParameterValue {
+    fn from(value: bool) -> Self {
+        ParameterValue::Boolean(value)
+    }
+}
+
+/// Parameter values keyed by parameter name, as passed to the renderer
+pub type ParameterValues = HashMap<String, ParameterValue>;
+
 impl Parameter {
+    /// The declared default as a typed value
+    pub fn default_value(&self) -> ParameterValue {
+        match self {
+            Parameter::String { default, .. } | Parameter::Enum { default, .. } => {
+                ParameterValue::String(default.clone())
+            }
+            Parameter::Boolean { default, .. } => ParameterValue::Boolean(*default),
+        }
+    }
+
+    /// This declaration as an inline TOML table, e.g.
+    /// `{ type = "boolean", default = true }`
+    fn to_inline_toml(&self) -> String {
+        let text = |value: &str| toml::Value::String(value.to_string()).to_string();
+        let mut fields = Vec::new();
+        let description = match self {
+            Parameter::String {
+                default,
+                pattern,
+                description,
+            } => {
+                fields.push(("type", text("string")));
+                fields.push(("default", text(default)));
+                if let Some(pattern) = pattern {
+                    fields.push(("pattern", text(pattern)));
+                }
+                description
+            }
+            Parameter::Boolean {
+                default,
+                description,
+            } => {
+                fields.push(("type", text("boolean")));
+                fields.push(("default", default.to_string()));
+                description
+            }
+            Parameter::Enum {
+                choices,
+                default,
+                description,
+            } => {
+                let choices: Vec<String> = choices.iter().map(|c| text(c)).collect();
+                fields.push(("type", text("enum")));
+                fields.push(("enum", format!("[{}]", choices.join(", "))));
+                fields.push(("default", text(default)));
+                description
+            }
+        };
+        if let Some(description) = description {
+            fields.push(("description", text(description)));
+        }
+
+        let fields: Vec<String> = fields
+            .into_iter()
+            .map(|(key, value)| format!("{} = {}", key, value))
+            .collect();
+        format!("{{ {} }}", fields.join(", "))
+    }
+
+    /// Validate a raw value and convert it to this parameter's type
+    pub fn parse_value(&self, value: &str) -> Result<ParameterValue, String> {
+        self.validate(value)?;
+
+        match self {
+            Parameter::Boolean { .. } => Ok(ParameterValue::Boolean(matches!(
+                value.to_lowercase().as_str(),
+                "true" | "yes" | "1"
+            ))),
+            Parameter::String { .. } | Parameter::Enum { .. } => {
+                Ok(ParameterValue::String(value.to_string()))
+            }
+        }
+    }
+
     /// Validate a value against this parameter's constraints
     pub fn validate(&self, value: &str) -> Result<(), String> {
         match self {
@@ -126,7 +295,7 @@ impl Parameter {
 }
 
 /// File inclusion/exclusion rules from `[files]` section of x402.toml.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FileRules {
     /// Glob patterns of files to include
     #[serde(default)]
@@ -135,6 +304,10 @@ pub struct FileRules {
     /// Glob patterns of files to exclude
     #[serde(default)]
     pub exclude: Vec<String>,
+
+    /// Glob patterns of files copied verbatim, without Liquid rendering
+    #[serde(default, alias = "copy_only", skip_serializing_if = "Vec::is_empty")]
+    pub raw: Vec<String>,
 }
 
 
@@ -178,4 +351,98 @@ mod tests {
         assert!(param.validate("false").is_ok());
         assert!(param.validate("invalid").is_err());
     }
+
+    #[test]
+    fn test_parse_value_types() {
+        let boolean = Parameter::Boolean {
+            default: true,
+            description: None,
+        };
+        assert_eq!(boolean.parse_value("No").unwrap(), ParameterValue::Boolean(false));
+        assert_eq!(boolean.parse_value("1").unwrap(), ParameterValue::Boolean(true));
+        assert!(boolean.parse_value("maybe").is_err());
+        assert_eq!(boolean.default_value(), ParameterValue::Boolean(true));
+
+        let choice = Parameter::Enum {
+            choices: vec!["postgres".to_string(), "sqlite".to_string()],
+            default: "sqlite".to_string(),
+            description: None,
+        };
+        assert_eq!(
+            choice.parse_value("postgres").unwrap(),
+            ParameterValue::String("postgres".to_string())
+        );
+        assert_eq!(choice.default_value().to_string(), "sqlite");
+    }
+
+    #[test]
+    fn test_to_toml_round_trips() {
+        let mut parameters = HashMap::new();
+        parameters.insert(
+            "network".to_string(),
+            Parameter::Enum {
+                choices: vec!["base-sepolia".to_string(), "base".to_string()],
+                default: "base-sepolia".to_string(),
+                description: Some("Chain to \"settle\" on".to_string()),
+            },
+        );
+        parameters.insert(
+            "enable_docker".to_string(),
+            Parameter::Boolean {
+                default: false,
+                description: None,
+            },
+        );
+        parameters.insert(
+            "service_name".to_string(),
+            Parameter::String {
+                default: "api".to_string(),
+                pattern: Some("^[a-z]+$".to_string()),
+                description: None,
+            },
+        );
+        let schema = TemplateSchema {
+            template: TemplateMetadata {
+                name: "Demo".to_string(),
+                description: "A demo template".to_string(),
+                version: "0.1.0".to_string(),
+                authors: vec!["Jo".to_string()],
+                repository: "https://github.com/jo/demo".to_string(),
+                tags: vec!["axum".to_string()],
+                min_rust_version: None,
+                min_x402_cli_version: None,
+            },
+            parameters: Some(parameters),
+            files: Some(FileRules {
+                include: vec![],
+                exclude: vec![".env".to_string()],
+                raw: vec![".github/**".to_string()],
+            }),
+        };
+
+        let text = schema.to_toml().unwrap();
+        assert!(text.starts_with("[template]\nname = \"Demo\"\n"));
+        assert!(text.contains("\n[parameters]\nenable_docker = { type = \"boolean\", default = false }\n"));
+        assert!(text.contains("service_name = { type = \"string\", default = \"api\", pattern = \"^[a-z]+$\" }"));
+        assert!(!text.contains("min_rust_version"));
+
+        let parsed: TemplateSchema = toml::from_str(&text).unwrap();
+        assert_eq!(parsed.template.tags, vec!["axum"]);
+        let files = parsed.files.unwrap();
+        assert_eq!(files.exclude, vec![".env"]);
+        assert_eq!(files.raw, vec![".github/**"]);
+        let params = parsed.parameters.unwrap();
+        assert_eq!(params.len(), 3);
+        match &params["network"] {
+            Parameter::Enum {
+                choices,
+                description,
+                ..
+            } => {
+                assert_eq!(choices.len(), 2);
+                assert_eq!(description.as_deref(), Some("Chain to \"settle\" on"));
+            }
+            other => panic!("expected enum, got {:?}", other),
+        }
+    }
 }
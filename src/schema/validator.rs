@@ -15,15 +15,18 @@ impl Validator {
         let content = std::fs::read_to_string(path)
             .map_err(|e| Error::FileSystemError(format!("Cannot read x402.toml: {}", e)))?;
 
-        let schema: TemplateSchema = toml::from_str(&content)
-            .map_err(|e| Error::TomlError(format!("Invalid TOML: {}", e)))?;
-
+        let schema = Self::parse(&content)?;
         Self::validate_schema(&schema)?;
         Ok(schema)
     }
 
+    /// Parse the contents of an x402.toml file without validating it
+    pub fn parse(content: &str) -> Result<TemplateSchema> {
+        toml::from_str(content).map_err(|e| Error::TomlError(format!("Invalid TOML: {}", e)))
+    }
+
     /// Validate schema structure and constraints
-    fn validate_schema(schema: &TemplateSchema) -> Result<()> {
+    pub fn validate_schema(schema: &TemplateSchema) -> Result<()> {
         let meta = &schema.template;
 
         // Required fields
@@ -117,6 +120,9 @@ impl Validator {
                     Self::validate_glob_pattern(pattern, "exclude")?;
                 }
             }
+            for pattern in &files.raw {
+                Self::validate_glob_pattern(pattern, "raw")?;
+            }
         }
 
         Ok(())
@@ -180,8 +186,6 @@ impl Validator {
 
     /// Validate glob pattern syntax
     fn validate_glob_pattern(pattern: &str, context: &str) -> Result<()> {
-        // Simple validation: check for common glob patterns
-        // More sophisticated validation could use the glob crate
         if pattern.is_empty() {
             return Err(Error::ValidationError {
                 field: format!("files.{}", context),
@@ -189,14 +193,13 @@ impl Validator {
             });
         }
 
-        // Valid glob patterns should contain at least one path component
-        if !pattern.contains('*') && !pattern.contains('?') && !pattern.contains('[') {
-            // It's a literal path, which is fine
-        }
+        glob::Pattern::new(pattern).map_err(|e| Error::ValidationError {
+            field: format!("files.{}", context),
+            message: format!("Invalid glob pattern '{}': {}", pattern, e),
+        })?;
 
         Ok(())
     }
-
 }
 
 #[cfg(test)]
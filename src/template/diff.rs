@@ -0,0 +1,156 @@
+//! Line diffs between a template file and what it renders to, for
+//! `create --dry-run --diff`
+
+/// Largest number of line pairs compared; bigger files are shown as fully
+/// replaced rather than taking quadratic time
+const MAX_CELLS: usize = 4_000_000;
+
+/// One line of a diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    /// Present in both
+    Same(&'a str),
+
+    /// Only in the template
+    Removed(&'a str),
+
+    /// Only in the rendered output
+    Added(&'a str),
+
+    /// This many unchanged lines left out
+    Elided(usize),
+}
+
+/// Diff two texts line by line, keeping `context` unchanged lines around
+/// each change and eliding the rest
+pub fn diff_lines<'a>(old: &'a str, new: &'a str, context: usize) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    elide(align(&old, &new), context)
+}
+
+/// Whether a diff has any changes
+pub fn has_changes(diff: &[DiffLine]) -> bool {
+    diff.iter()
+        .any(|line| matches!(line, DiffLine::Removed(_) | DiffLine::Added(_)))
+}
+
+/// Pair up the lines of a longest common subsequence
+fn align<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    // Common prefix and suffix need no table
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().map(|l| DiffLine::Same(l)).collect();
+    if a.len().saturating_mul(b.len()) > MAX_CELLS {
+        lines.extend(a.iter().map(|l| DiffLine::Removed(l)));
+        lines.extend(b.iter().map(|l| DiffLine::Added(l)));
+    } else {
+        // lengths[i][j]: LCS length of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lengths = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lengths[i * width + j] = if a[i] == b[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                lines.push(DiffLine::Same(a[i]));
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                lines.push(DiffLine::Removed(a[i]));
+                i += 1;
+            } else {
+                lines.push(DiffLine::Added(b[j]));
+                j += 1;
+            }
+        }
+        lines.extend(a[i..].iter().map(|l| DiffLine::Removed(l)));
+        lines.extend(b[j..].iter().map(|l| DiffLine::Added(l)));
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(|l| DiffLine::Same(l)));
+    lines
+}
+
+/// Replace runs of unchanged lines further than `context` from any change
+/// with [`DiffLine::Elided`]
+fn elide(lines: Vec<DiffLine>, context: usize) -> Vec<DiffLine> {
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let near_change = |i: usize| {
+        let at = changed.partition_point(|&c| c + context < i);
+        changed.get(at).is_some_and(|&c| c <= i + context)
+    };
+
+    let mut out = Vec::new();
+    let mut elided = 0;
+    for (i, line) in lines.into_iter().enumerate() {
+        if matches!(line, DiffLine::Same(_)) && !near_change(i) {
+            elided += 1;
+            continue;
+        }
+        if elided > 0 {
+            out.push(DiffLine::Elided(elided));
+            elided = 0;
+        }
+        out.push(line);
+    }
+    if elided > 0 {
+        out.push(DiffLine::Elided(elided));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "a\nb\nname = \"{{ project_name }}\"\nc\nd\ne\nf\n{% if x %}\ng\n{% endif %}\n";
+        let new = "a\nb\nname = \"my-app\"\nc\nd\ne\nf\ng\n";
+        let diff = diff_lines(old, new, 1);
+
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Elided(1),
+                DiffLine::Same("b"),
+                DiffLine::Removed("name = \"{{ project_name }}\""),
+                DiffLine::Added("name = \"my-app\""),
+                DiffLine::Same("c"),
+                DiffLine::Elided(2),
+                DiffLine::Same("f"),
+                DiffLine::Removed("{% if x %}"),
+                DiffLine::Same("g"),
+                DiffLine::Removed("{% endif %}"),
+            ]
+        );
+        assert!(has_changes(&diff));
+    }
+
+    #[test]
+    fn test_diff_without_changes() {
+        let diff = diff_lines("one\ntwo\nthree\n", "one\ntwo\nthree\n", 3);
+        assert!(!has_changes(&diff));
+        assert_eq!(diff_lines("x\n", "x\n", 0), vec![DiffLine::Elided(1)]);
+    }
+}
@@ -1,36 +1,239 @@
-//! Template downloading from GitHub
+//! Template downloading from GitHub and archive URLs
 
-use crate::error::{Error, Result};
-use std::path::Path;
+use crate::discovery::{http, GitHubHost};
+use crate::error::{ArchiveViolation, Error, Result};
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
 use walkdir::WalkDir;
 
+/// Largest archive downloaded unless [`Downloader::with_max_download_size`]
+/// says otherwise
+pub const DEFAULT_MAX_DOWNLOAD_SIZE: u64 = 100 * 1024 * 1024;
+
+/// How long a download may go without receiving data unless
+/// [`Downloader::with_stall_timeout`] says otherwise
+pub const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Extracted bytes allowed before the compression ratio is checked, so tiny
+/// archives of repetitive text are not mistaken for zip bombs
+const RATIO_GRACE_BYTES: u64 = 1024 * 1024;
+
+/// Longest symlink target read from a zip entry
+const MAX_LINK_TARGET: u64 = 4096;
+
+/// Archive formats understood by [`Downloader::download_archive`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// `.zip`
+    Zip,
+    /// `.tar.gz` / `.tgz`
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Guess the format from a URL's file extension
+    pub fn from_url(url: &str) -> Option<Self> {
+        let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+        if path.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// Caps on what extracting one archive may write
+///
+/// Templates are small, so the defaults leave plenty of room while stopping
+/// zip bombs long before they fill the disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractLimits {
+    /// Most bytes all extracted files may add up to
+    pub max_total_size: u64,
+
+    /// Most entries (files, directories and links) an archive may hold
+    pub max_entries: usize,
+
+    /// Most extracted bytes per archive byte, once more than 1 MiB is out
+    pub max_ratio: u64,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_total_size: 256 * 1024 * 1024,
+            max_entries: 10_000,
+            max_ratio: 100,
+        }
+    }
+}
+
 /// Downloads and extracts templates
+///
+/// Archives are streamed to a temporary file rather than held in memory,
+/// with a progress bar on the terminal. A download larger than the maximum
+/// size, or one that receives nothing for the stall timeout, is aborted.
+///
+/// Extraction never writes outside the destination: entries with absolute
+/// paths or `..` components are refused, as are symlinks pointing outside
+/// the template and hard links to anything but a file extracted earlier.
+/// Symlinks that stay inside the template are recreated and hard links
+/// become copies. Each refusal is an [`Error::UnsafeArchive`].
 pub struct Downloader {
     client: reqwest::Client,
+    host: GitHubHost,
+    limits: ExtractLimits,
+    max_download_size: u64,
+    stall_timeout: Duration,
 }
 
 impl Downloader {
-    /// Create a new downloader
+    /// Create a new downloader for anonymous access to github.com
     pub fn new() -> Self {
+        Self::for_host(GitHubHost::public())
+    }
+
+    /// Create a downloader that fetches zipballs from a different API base
+    /// URL, such as a GitHub mirror or a local mock server
+    #[allow(dead_code)] // Intentionally public for library users
+    pub fn with_base_url(api_url: &str) -> Self {
+        Self::for_host(GitHubHost::with_api_url(api_url))
+    }
+
+    /// Create a downloader for a GitHub or GitHub Enterprise host,
+    /// authenticating zipball requests with the host's token
+    pub fn for_host(host: GitHubHost) -> Self {
         Self {
             client: reqwest::Client::new(),
+            host,
+            limits: ExtractLimits::default(),
+            max_download_size: DEFAULT_MAX_DOWNLOAD_SIZE,
+            stall_timeout: DEFAULT_STALL_TIMEOUT,
         }
     }
 
-    /// Download template from GitHub URL and extract to destination
-    pub async fn download(&self, template_url: &str, dest: &Path) -> Result<()> {
-        // Normalize template URL
-        let url = Self::normalize_github_url(template_url)?;
-        let zipball_url = Self::github_to_zipball_url(&url)?;
+    /// Use different extraction limits
+    #[allow(dead_code)] // Intentionally public for library users
+    pub fn with_limits(mut self, limits: ExtractLimits) -> Self {
+        self.limits = limits;
+        self
+    }
 
-        // Download ZIP file
-        let response = self
-            .client
-            .get(&zipball_url)
-            .header("User-Agent", "cargo-x402")
-            .send()
+    /// Abort downloads larger than `bytes`
+    #[allow(dead_code)] // Intentionally public for library users
+    pub fn with_max_download_size(mut self, bytes: u64) -> Self {
+        self.max_download_size = bytes;
+        self
+    }
+
+    /// Abort downloads that receive no data for `timeout`
+    #[allow(dead_code)] // Intentionally public for library users
+    pub fn with_stall_timeout(mut self, timeout: Duration) -> Self {
+        self.stall_timeout = timeout;
+        self
+    }
+
+    /// Download a repository's zipball at the given branch, tag or commit
+    /// and extract to destination
+    ///
+    /// The zipball is fetched through the REST API so that private
+    /// repositories work with a token.
+    #[allow(dead_code)] // Intentionally public for library users
+    pub async fn download(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        dest: &Path,
+    ) -> Result<()> {
+        let archive = self.fetch(self.zipball_request(owner, repo, reference)).await?;
+        self.extract_from(std::io::BufReader::new(archive), ArchiveFormat::Zip, dest)
+    }
+
+    /// Download a repository's zipball to a file without extracting it
+    pub async fn download_zipball(
+        &self,
+        owner: &str,
+        repo: &str,
+        reference: &str,
+        file: &Path,
+    ) -> Result<()> {
+        let mut out = std::fs::File::create(file)
+            .map_err(|e| Error::FileSystemError(format!("Cannot save archive: {}", e)))?;
+        self.fetch_into(self.zipball_request(owner, repo, reference), &mut out)
             .await
-            .map_err(|e| Error::NetworkError(format!("Failed to download template: {}", e)))?;
+    }
+
+    /// Download a `.zip` or `.tar.gz` archive from any URL and extract it
+    /// to destination
+    ///
+    /// A single top-level directory in the archive (as produced by GitHub
+    /// and most release tooling) is unwrapped. No credentials are sent.
+    pub async fn download_archive(
+        &self,
+        archive_url: &str,
+        format: ArchiveFormat,
+        dest: &Path,
+    ) -> Result<()> {
+        let request = self
+            .client
+            .get(archive_url)
+            .header("User-Agent", "cargo-x402");
+
+        let archive = self.fetch(request).await?;
+        self.extract_from(std::io::BufReader::new(archive), format, dest)
+    }
+
+    /// Extract an archive file on disk to destination
+    pub fn extract(&self, archive: &Path, format: ArchiveFormat, dest: &Path) -> Result<()> {
+        let file = std::fs::File::open(archive)
+            .map_err(|e| Error::FileSystemError(format!("Cannot open archive: {}", e)))?;
+        self.extract_from(std::io::BufReader::new(file), format, dest)
+    }
+
+    fn zipball_request(&self, owner: &str, repo: &str, reference: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .get(self.zipball_url(owner, repo, reference))
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", "cargo-x402");
+        self.host.authorize(request)
+    }
+
+    /// Download to an anonymous temporary file, rewound for reading
+    async fn fetch(&self, request: reqwest::RequestBuilder) -> Result<std::fs::File> {
+        let mut file = tempfile::tempfile()
+            .map_err(|e| Error::FileSystemError(format!("Cannot create temp file: {}", e)))?;
+        self.fetch_into(request, &mut file).await?;
+        file.rewind()
+            .map_err(|e| Error::FileSystemError(format!("Cannot read download: {}", e)))?;
+        Ok(file)
+    }
+
+    /// Stream a response body into `file` chunk by chunk
+    async fn fetch_into(
+        &self,
+        request: reqwest::RequestBuilder,
+        file: &mut std::fs::File,
+    ) -> Result<()> {
+        let stalled = || {
+            Error::NetworkError(format!(
+                "Download stalled: no data received for {}s",
+                self.stall_timeout.as_secs_f64()
+            ))
+        };
+        let too_large = || {
+            Error::NetworkError(format!(
+                "Download is larger than the {} limit",
+                HumanBytes(self.max_download_size)
+            ))
+        };
+
+        let mut response = http::send_with_timeout(request, self.stall_timeout).await?;
 
         if !response.status().is_success() {
             return Err(Error::NetworkError(format!(
@@ -39,25 +242,57 @@ impl Downloader {
             )));
         }
 
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| Error::NetworkError(format!("Failed to read response: {}", e)))?;
+        let length = response.content_length();
+        if length.is_some_and(|length| length > self.max_download_size) {
+            return Err(too_large());
+        }
+        let progress = download_progress(length);
+
+        let mut received = 0u64;
+        loop {
+            let chunk = tokio::time::timeout(self.stall_timeout, response.chunk())
+                .await
+                .map_err(|_| stalled())?
+                .map_err(|e| Error::NetworkError(format!("Failed to read response: {}", e)))?;
+            let Some(chunk) = chunk else { break };
+
+            received += chunk.len() as u64;
+            if received > self.max_download_size {
+                progress.abandon();
+                return Err(too_large());
+            }
+            file.write_all(&chunk)
+                .map_err(|e| Error::FileSystemError(format!("Cannot save download: {}", e)))?;
+            progress.set_position(received);
+        }
 
-        // Extract ZIP to temporary location first
+        progress.finish_and_clear();
+        Ok(())
+    }
+
+    fn extract_from<R: Read + Seek>(
+        &self,
+        mut data: R,
+        format: ArchiveFormat,
+        dest: &Path,
+    ) -> Result<()> {
+        // Extract archive to temporary location first
         let temp_extract = tempfile::TempDir::new()
             .map_err(|e| Error::FileSystemError(format!("Cannot create temp dir: {}", e)))?;
 
-        let zip_data = std::io::Cursor::new(bytes);
-        let mut archive = zip::ZipArchive::new(zip_data)
-            .map_err(|e| Error::FileSystemError(format!("Invalid ZIP file: {}", e)))?;
-
-        archive
-            .extract(temp_extract.path())
-            .map_err(|e| Error::FileSystemError(format!("Failed to extract ZIP: {}", e)))?;
+        let archive_size = data
+            .seek(SeekFrom::End(0))
+            .and_then(|size| data.rewind().map(|_| size))
+            .map_err(|e| Error::FileSystemError(format!("Cannot read archive: {}", e)))?;
+        let mut extraction = Extraction::new(temp_extract.path(), self.limits, archive_size);
+        match format {
+            ArchiveFormat::Zip => extraction.unpack_zip(data)?,
+            ArchiveFormat::TarGz => extraction.unpack_tar_gz(data)?,
+        }
+        extraction.create_links()?;
 
-        // The extracted directory has a format like {repo-commit}/, find it
-        let extracted_dir = self.find_extracted_directory(temp_extract.path())?;
+        // Archives usually wrap everything in a {repo-commit}/ directory
+        let extracted_dir = Self::find_extracted_directory(temp_extract.path())?;
 
         // Create destination and copy files (except .git)
         std::fs::create_dir_all(dest)
@@ -68,26 +303,27 @@ impl Downloader {
         Ok(())
     }
 
-    /// Find the extracted directory (usually named {repo}-{hash})
-    fn find_extracted_directory(&self, temp_path: &Path) -> Result<std::path::PathBuf> {
-        for entry in std::fs::read_dir(temp_path)
+    /// Find the template root: the single top-level directory when the
+    /// archive has one, otherwise the extraction root itself
+    fn find_extracted_directory(temp_path: &Path) -> Result<std::path::PathBuf> {
+        let entries: Vec<_> = std::fs::read_dir(temp_path)
             .map_err(|e| Error::FileSystemError(format!("Cannot read temp dir: {}", e)))?
-        {
-            let entry = entry
-                .map_err(|e| Error::FileSystemError(format!("Cannot read entry: {}", e)))?;
-            let path = entry.path();
+            .collect::<std::io::Result<_>>()
+            .map_err(|e| Error::FileSystemError(format!("Cannot read entry: {}", e)))?;
 
-            if path.is_dir() {
-                return Ok(path);
-            }
+        match entries.as_slice() {
+            [] => Err(Error::FileSystemError(
+                "Downloaded archive is empty".to_string(),
+            )),
+            [single] if single.file_type().is_ok_and(|t| t.is_dir()) => Ok(single.path()),
+            _ => Ok(temp_path.to_path_buf()),
         }
-
-        Err(Error::FileSystemError(
-            "No directory found in extracted archive".to_string(),
-        ))
     }
 
     /// Recursively copy directory tree, excluding .git
+    ///
+    /// Permissions are copied with each file; symlinks, already checked
+    /// during extraction, are recreated rather than followed.
     fn copy_tree(src: &Path, dest: &Path) -> Result<()> {
         for entry in WalkDir::new(src)
             .into_iter()
@@ -100,9 +336,13 @@ impl Downloader {
                 .map_err(|e| Error::FileSystemError(e.to_string()))?;
             let dest_path = dest.join(rel_path);
 
-            if entry.path().is_dir() {
+            if entry.file_type().is_dir() {
                 std::fs::create_dir_all(&dest_path)
                     .map_err(|e| Error::FileSystemError(format!("Cannot create dir: {}", e)))?;
+            } else if entry.file_type().is_symlink() {
+                let target = std::fs::read_link(entry.path())
+                    .map_err(|e| Error::FileSystemError(format!("Cannot read link: {}", e)))?;
+                symlink(&target, &dest_path)?;
             } else {
                 std::fs::copy(entry.path(), &dest_path).map_err(|e| {
                     Error::FileSystemError(format!("Cannot copy file: {}", e))
@@ -113,67 +353,379 @@ impl Downloader {
         Ok(())
     }
 
-    /// Normalize GitHub URL (handle shorthand and full URLs)
-    fn normalize_github_url(url: &str) -> Result<String> {
-        // If it's a shorthand (user/repo), convert to full GitHub URL
-        if !url.contains('/') {
-            return Err(Error::ValidationError {
-                field: "template".to_string(),
-                message: "Template URL must be in format 'user/repo' or full GitHub URL".to_string(),
-            });
+    /// API endpoint serving a repository's zipball
+    fn zipball_url(&self, owner: &str, repo: &str, reference: &str) -> String {
+        // GitHub resolves branches, tags and commit SHAs here
+        format!(
+            "{}/repos/{}/{}/zipball/{}",
+            self.host.api_url, owner, repo, reference
+        )
+    }
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Byte progress for a download, as a bar when the size is known
+///
+/// Hidden automatically when stderr is not a terminal.
+fn download_progress(length: Option<u64>) -> ProgressBar {
+    let (progress, template) = match length {
+        Some(length) => (
+            ProgressBar::new(length),
+            "{spinner:.cyan} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+        ),
+        None => (
+            ProgressBar::new_spinner(),
+            "{spinner:.cyan} {bytes} ({bytes_per_sec})",
+        ),
+    };
+    if let Ok(style) = ProgressStyle::with_template(template) {
+        progress.set_style(style.progress_chars("=> "));
+    }
+    progress.enable_steady_tick(Duration::from_millis(80));
+    progress
+}
+
+/// What to do with an entry, decided before anything is written
+enum EntryKind {
+    Directory,
+    /// Regular file, with its unix mode if the archive recorded one
+    File(Option<u32>),
+    /// Symbolic link, with its target relative to the link's directory
+    Symlink(PathBuf),
+    /// Hard link, with the path of an earlier entry
+    HardLink(PathBuf),
+    /// Devices, FIFOs and other special files
+    Other,
+}
+
+/// A symlink entry, created once the whole archive is unpacked
+struct PendingLink {
+    name: String,
+    path: PathBuf,
+    target: PathBuf,
+}
+
+/// One archive being unpacked into a directory, entry by entry
+struct Extraction<'a> {
+    root: &'a Path,
+    limits: ExtractLimits,
+    entries: usize,
+    written: u64,
+    /// Bytes that may be written in total: the size limit, or less when the
+    /// ratio limit is stricter for this archive
+    budget: u64,
+    /// Symlinks wait until the template root is known, since a wrapper
+    /// directory unwrapped later changes where `..` leads
+    links: Vec<PendingLink>,
+}
+
+impl<'a> Extraction<'a> {
+    fn new(root: &'a Path, limits: ExtractLimits, archive_size: u64) -> Self {
+        let by_ratio = archive_size
+            .saturating_mul(limits.max_ratio)
+            .max(RATIO_GRACE_BYTES);
+        Self {
+            root,
+            limits,
+            entries: 0,
+            written: 0,
+            budget: limits.max_total_size.min(by_ratio),
+            links: Vec::new(),
         }
+    }
 
-        if url.starts_with("https://github.com/") {
-            return Ok(url.to_string());
+    fn unpack_zip<R: Read + Seek>(&mut self, data: R) -> Result<()> {
+        let mut archive = zip::ZipArchive::new(data)
+            .map_err(|e| Error::FileSystemError(format!("Invalid ZIP file: {}", e)))?;
+        if archive.len() > self.limits.max_entries {
+            return Err(self.too_many_entries());
         }
 
-        if url.contains('/') && !url.contains("://") {
-            // Assume it's a shorthand like "user/repo"
-            return Ok(format!("https://github.com/{}", url));
+        for index in 0..archive.len() {
+            let mut file = archive
+                .by_index(index)
+                .map_err(|e| Error::FileSystemError(format!("Failed to extract ZIP: {}", e)))?;
+            let name = file.name().to_string();
+            // S_IFLNK: the entry's content is the link target
+            let is_link = file.unix_mode().is_some_and(|mode| mode & 0o170000 == 0o120000);
+            let kind = if is_link {
+                let mut target = String::new();
+                (&mut file)
+                    .take(MAX_LINK_TARGET)
+                    .read_to_string(&mut target)
+                    .map_err(|e| Error::FileSystemError(format!("Failed to extract ZIP: {}", e)))?;
+                EntryKind::Symlink(PathBuf::from(target))
+            } else if file.is_dir() {
+                EntryKind::Directory
+            } else {
+                EntryKind::File(file.unix_mode())
+            };
+            self.unpack_entry(&name, kind, &mut file)?;
         }
+        Ok(())
+    }
 
-        Err(Error::ValidationError {
-            field: "template".to_string(),
-            message: "Template URL must be a GitHub URL or 'user/repo' shorthand".to_string(),
-        })
+    fn unpack_tar_gz<R: Read>(&mut self, data: R) -> Result<()> {
+        let read_error = |e: std::io::Error| {
+            Error::FileSystemError(format!("Failed to extract tarball: {}", e))
+        };
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+
+        for entry in archive.entries().map_err(read_error)? {
+            let mut entry = entry.map_err(read_error)?;
+            let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+            let entry_type = entry.header().entry_type();
+            let kind = if entry_type.is_dir() {
+                EntryKind::Directory
+            } else if entry_type.is_file() {
+                EntryKind::File(entry.header().mode().ok())
+            } else if entry_type.is_symlink() || entry_type.is_hard_link() {
+                let target = entry.link_name().map_err(read_error)?.unwrap_or_default();
+                if entry_type.is_symlink() {
+                    EntryKind::Symlink(target.into_owned())
+                } else {
+                    EntryKind::HardLink(target.into_owned())
+                }
+            } else if entry_type.is_pax_global_extensions()
+                || entry_type.is_pax_local_extensions()
+                || entry_type.is_gnu_longname()
+                || entry_type.is_gnu_longlink()
+            {
+                // Metadata for other entries, already applied by `tar`
+                continue;
+            } else {
+                EntryKind::Other
+            };
+            self.unpack_entry(&name, kind, &mut entry)?;
+        }
+        Ok(())
+    }
+
+    fn unpack_entry(&mut self, name: &str, kind: EntryKind, data: &mut impl Read) -> Result<()> {
+        self.entries += 1;
+        if self.entries > self.limits.max_entries {
+            return Err(self.too_many_entries());
+        }
+        let path = entry_path(name)?;
+        self.check_not_through_link(name, &path)?;
+        let unsafe_link = |target: &Path| unsafe_link(name, target);
+
+        match kind {
+            EntryKind::Directory => create_dir(&self.root.join(path)),
+            EntryKind::File(mode) => {
+                self.write_file(&path, data)?;
+                set_mode(&self.root.join(path), mode)
+            }
+            EntryKind::Symlink(target) => {
+                // Checked against the archive root now, and against the
+                // template root once every entry is out
+                let parent = path.parent().unwrap_or(Path::new(""));
+                if !stays_inside(parent, &target) {
+                    return Err(unsafe_link(&target));
+                }
+                self.links.push(PendingLink {
+                    name: name.to_string(),
+                    path,
+                    target,
+                });
+                Ok(())
+            }
+            EntryKind::HardLink(target) => {
+                // Recreated as a copy of the earlier entry it names
+                let source = entry_path(&target.to_string_lossy())
+                    .map_err(|_| unsafe_link(&target))?;
+                self.check_not_through_link(name, &source)?;
+                let source = self.root.join(source);
+                if !source.symlink_metadata().is_ok_and(|m| m.is_file()) {
+                    return Err(unsafe_link(&target));
+                }
+                let mode = std::fs::metadata(&source).ok().map(|m| m.permissions());
+                let mut file = std::fs::File::open(&source)
+                    .map_err(|e| Error::FileSystemError(format!("Cannot read entry: {}", e)))?;
+                self.write_file(&path, &mut file)?;
+                if let Some(mode) = mode {
+                    std::fs::set_permissions(self.root.join(path), mode).map_err(|e| {
+                        Error::FileSystemError(format!("Cannot set permissions: {}", e))
+                    })?;
+                }
+                Ok(())
+            }
+            EntryKind::Other => Ok(()),
+        }
+    }
+
+    /// Refuse entries at or below a link seen earlier: each link is checked
+    /// on its own, but writing through one could chain out of the archive
+    /// (`a -> ..`, then `a/b -> ..`)
+    fn check_not_through_link(&self, name: &str, path: &Path) -> Result<()> {
+        if self.links.iter().any(|link| path.starts_with(&link.path)) {
+            return Err(Error::UnsafeArchive(ArchiveViolation::UnsafePath {
+                entry: name.to_string(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Create the symlinks collected while unpacking, refusing any that
+    /// leave the template once a single top-level directory is unwrapped
+    fn create_links(&mut self) -> Result<()> {
+        let wrapper = self.wrapper_directory()?;
+        for link in std::mem::take(&mut self.links) {
+            let inside = match &wrapper {
+                Some(wrapper) => link.path.strip_prefix(wrapper).ok(),
+                None => Some(link.path.as_path()),
+            };
+            let parent = inside.and_then(Path::parent).unwrap_or(Path::new(""));
+            if inside.is_none() || !stays_inside(parent, &link.target) {
+                return Err(unsafe_link(&link.name, &link.target));
+            }
+
+            let path = self.root.join(&link.path);
+            if let Some(parent) = path.parent() {
+                create_dir(parent)?;
+            }
+            symlink(&link.target, &path)?;
+        }
+        Ok(())
     }
 
-    /// Convert GitHub URL to zipball URL for download
-    fn github_to_zipball_url(github_url: &str) -> Result<String> {
-        // Expected format: https://github.com/owner/repo
-        let url = github_url.trim_end_matches('/');
+    /// The single top-level directory that will be unwrapped as the
+    /// template root, if the archive has one
+    fn wrapper_directory(&self) -> Result<Option<PathBuf>> {
+        let mut top = std::collections::BTreeSet::new();
+        for entry in std::fs::read_dir(self.root)
+            .map_err(|e| Error::FileSystemError(format!("Cannot read temp dir: {}", e)))?
+        {
+            let entry =
+                entry.map_err(|e| Error::FileSystemError(format!("Cannot read entry: {}", e)))?;
+            top.insert(PathBuf::from(entry.file_name()));
+        }
+        for link in &self.links {
+            if let Some(first) = link.path.components().next() {
+                top.insert(PathBuf::from(first.as_os_str()));
+            }
+        }
+
+        Ok(match top.into_iter().collect::<Vec<_>>().as_slice() {
+            [single] if self.root.join(single).is_dir() => Some(single.clone()),
+            _ => None,
+        })
+    }
 
-        if !url.starts_with("https://github.com/") {
-            return Err(Error::ValidationError {
-                field: "template".to_string(),
-                message: "Must be a GitHub URL".to_string(),
-            });
+    /// Write one file, counting the bytes actually produced rather than
+    /// trusting the sizes the archive claims
+    fn write_file(&mut self, path: &Path, data: &mut impl Read) -> Result<()> {
+        let target = self.root.join(path);
+        if let Some(parent) = target.parent() {
+            create_dir(parent)?;
         }
+        let mut file = std::fs::File::create(&target)
+            .map_err(|e| Error::FileSystemError(format!("Cannot create file: {}", e)))?;
 
-        // Extract owner/repo
-        let parts: Vec<&str> = url.split('/').collect();
-        if parts.len() < 5 {
-            return Err(Error::ValidationError {
-                field: "template".to_string(),
-                message: "Invalid GitHub URL format".to_string(),
-            });
+        let remaining = self.budget - self.written;
+        let copied = std::io::copy(&mut data.take(remaining + 1), &mut file)
+            .map_err(|e| Error::FileSystemError(format!("Failed to extract archive: {}", e)))?;
+        self.written += copied;
+        if self.written > self.budget {
+            let violation = if self.budget < self.limits.max_total_size {
+                ArchiveViolation::CompressionRatio {
+                    limit: self.limits.max_ratio,
+                }
+            } else {
+                ArchiveViolation::TooLarge {
+                    limit: self.limits.max_total_size,
+                }
+            };
+            return Err(Error::UnsafeArchive(violation));
         }
+        Ok(())
+    }
 
-        let owner = parts[3];
-        let repo = parts[4];
+    fn too_many_entries(&self) -> Error {
+        Error::UnsafeArchive(ArchiveViolation::TooManyEntries {
+            limit: self.limits.max_entries,
+        })
+    }
+}
 
-        // GitHub zipball URL for main branch
-        Ok(format!(
-            "https://github.com/{}/{}/archive/refs/heads/main.zip",
-            owner, repo
-        ))
+/// Where an entry goes relative to the extraction root, refusing names that
+/// would land outside it
+fn entry_path(name: &str) -> Result<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(Error::UnsafeArchive(ArchiveViolation::UnsafePath {
+                    entry: name.to_string(),
+                }))
+            }
+        }
     }
+    Ok(path)
 }
 
-impl Default for Downloader {
-    fn default() -> Self {
-        Self::new()
+fn unsafe_link(entry: &str, target: &Path) -> Error {
+    Error::UnsafeArchive(ArchiveViolation::UnsafeLink {
+        entry: entry.to_string(),
+        target: target.display().to_string(),
+    })
+}
+
+/// Whether a link in `dir` (relative to some root) pointing at `target`
+/// resolves to somewhere inside that root, without touching the disk
+pub(crate) fn stays_inside(dir: &Path, target: &Path) -> bool {
+    let mut depth = dir.components().count();
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return false,
+        }
     }
+    true
+}
+
+/// Apply a unix mode recorded in an archive, keeping only permission bits
+/// (never setuid or setgid) and always leaving the file readable and
+/// writable by its owner
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: Option<u32>) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let Some(mode) = mode else { return Ok(()) };
+    let permissions = std::fs::Permissions::from_mode(mode & 0o777 | 0o600);
+    std::fs::set_permissions(path, permissions)
+        .map_err(|e| Error::FileSystemError(format!("Cannot set permissions: {}", e)))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: Option<u32>) -> Result<()> {
+    Ok(())
+}
+
+/// Create a symlink; platforms without unix symlinks leave it out
+#[cfg(unix)]
+pub(crate) fn symlink(target: &Path, link: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, link)
+        .map_err(|e| Error::FileSystemError(format!("Cannot create link: {}", e)))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn symlink(_target: &Path, _link: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn create_dir(path: &Path) -> Result<()> {
+    std::fs::create_dir_all(path)
+        .map_err(|e| Error::FileSystemError(format!("Cannot create dir: {}", e)))
 }
 
 #[cfg(test)]
@@ -181,24 +733,421 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_normalize_shorthand_url() {
-        let result = Downloader::normalize_github_url("user/repo").unwrap();
-        assert_eq!(result, "https://github.com/user/repo");
+    fn test_zipball_url() {
+        let downloader = Downloader::new();
+        assert_eq!(
+            downloader.zipball_url("user", "my-repo", "v1.2.0"),
+            "https://api.github.com/repos/user/my-repo/zipball/v1.2.0"
+        );
+
+        let enterprise = Downloader::with_base_url("https://github.example.com/api/v3/");
+        assert_eq!(
+            enterprise.zipball_url("team", "payments", "main"),
+            "https://github.example.com/api/v3/repos/team/payments/zipball/main"
+        );
+    }
+
+    #[test]
+    fn test_archive_format_from_url() {
+        assert_eq!(
+            ArchiveFormat::from_url("https://example.com/t.zip"),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(
+            ArchiveFormat::from_url("https://example.com/t.tar.gz?token=1"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_url("https://example.com/t.TGZ"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(ArchiveFormat::from_url("https://example.com/t"), None);
     }
 
     #[test]
-    fn test_normalize_full_url() {
-        let url = "https://github.com/user/repo";
-        let result = Downloader::normalize_github_url(url).unwrap();
-        assert_eq!(result, url);
+    fn test_find_extracted_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("repo-abc123")).unwrap();
+        assert_eq!(
+            Downloader::find_extracted_directory(dir.path()).unwrap(),
+            dir.path().join("repo-abc123")
+        );
+
+        std::fs::write(dir.path().join("x402.toml"), "").unwrap();
+        assert_eq!(
+            Downloader::find_extracted_directory(dir.path()).unwrap(),
+            dir.path()
+        );
+    }
+
+    fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, content) in files {
+            writer
+                .start_file(*path, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_download_sends_token() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/repos/team/payments/zipball/v1")
+            .match_header("authorization", "Bearer secret")
+            .with_body(zip(&[("team-payments-abc/x402.toml", "[template]")]))
+            .create_async()
+            .await;
+
+        let downloader = Downloader::for_host(GitHubHost {
+            api_url: server.url(),
+            token: Some("secret".to_string()),
+            ..GitHubHost::public()
+        });
+        let dest = tempfile::TempDir::new().unwrap();
+        downloader
+            .download("team", "payments", "v1", dest.path())
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert!(dest.path().join("x402.toml").is_file());
+    }
+
+    /// A tarball built from raw headers, since `tar::Builder` refuses to
+    /// write the unsafe paths these tests need
+    fn tar_gz(entries: &[(&str, tar::EntryType, &str)]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, entry_type, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_entry_type(*entry_type);
+            header.set_mode(0o644);
+            if entry_type.is_file() {
+                header.set_size(content.len() as u64);
+                header.set_cksum();
+                builder.append(&header, content.as_bytes()).unwrap();
+            } else {
+                header.set_link_name_literal(content).unwrap();
+                header.set_size(0);
+                header.set_cksum();
+                builder.append(&header, std::io::empty()).unwrap();
+            }
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    /// Serve an archive from a local mock server and extract it
+    async fn extract_served(
+        downloader: Downloader,
+        body: Vec<u8>,
+        format: ArchiveFormat,
+    ) -> (tempfile::TempDir, Result<()>) {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/template")
+            .with_body(body)
+            .create_async()
+            .await;
+        let dest = tempfile::TempDir::new().unwrap();
+        let url = format!("{}/template", server.url());
+        let result = downloader
+            .download_archive(&url, format, &dest.path().join("out"))
+            .await;
+        (dest, result)
+    }
+
+    fn violation(result: Result<()>) -> ArchiveViolation {
+        match result {
+            Err(Error::UnsafeArchive(violation)) => violation,
+            other => panic!("expected an unsafe archive error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_rejects_path_traversal() {
+        for name in ["../evil.sh", "t/../../evil.sh", "/tmp/evil.sh"] {
+            let body = zip(&[("t/x402.toml", "[template]"), (name, "pwned")]);
+            let (dest, result) = extract_served(Downloader::new(), body, ArchiveFormat::Zip).await;
+            assert_eq!(
+                violation(result),
+                ArchiveViolation::UnsafePath {
+                    entry: name.to_string()
+                }
+            );
+            assert!(!dest.path().join("evil.sh").exists());
+
+            let body = tar_gz(&[(name, tar::EntryType::Regular, "pwned")]);
+            let (_, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+            assert!(matches!(violation(result), ArchiveViolation::UnsafePath { .. }));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_checks_links() {
+        use std::io::Write;
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        writer.start_file("t/x402.toml", options).unwrap();
+        writer.write_all(b"[template]").unwrap();
+        writer.add_symlink("t/docs/readme", "../x402.toml", options).unwrap();
+        let body = writer.finish().unwrap().into_inner();
+        let (dest, result) = extract_served(Downloader::new(), body, ArchiveFormat::Zip).await;
+        result.unwrap();
+        assert!(dest.path().join("out/x402.toml").is_file());
+        #[cfg(unix)]
+        assert_eq!(
+            std::fs::read_link(dest.path().join("out/docs/readme")).unwrap(),
+            Path::new("../x402.toml")
+        );
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.add_symlink("t/passwd", "../../etc/passwd", options).unwrap();
+        let body = writer.finish().unwrap().into_inner();
+        let (_, result) = extract_served(Downloader::new(), body, ArchiveFormat::Zip).await;
+        assert_eq!(
+            violation(result),
+            ArchiveViolation::UnsafeLink {
+                entry: "t/passwd".to_string(),
+                target: "../../etc/passwd".to_string()
+            }
+        );
+
+        for entry_type in [tar::EntryType::Symlink, tar::EntryType::Link] {
+            let body = tar_gz(&[
+                ("t/x402.toml", tar::EntryType::Regular, "[template]"),
+                ("t/key", entry_type, "/home/user/.ssh/id_ed25519"),
+            ]);
+            let (_, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+            assert!(matches!(violation(result), ArchiveViolation::UnsafeLink { .. }));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extract_refuses_writing_through_links() {
+        // Each link stays inside on its own, but `t/up/out` would be `../`
+        let body = tar_gz(&[
+            ("t/up", tar::EntryType::Symlink, ".."),
+            ("t/up/out", tar::EntryType::Symlink, ".."),
+        ]);
+        let (dest, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+        assert_eq!(
+            violation(result),
+            ArchiveViolation::UnsafePath {
+                entry: "t/up/out".to_string()
+            }
+        );
+        assert!(!dest.path().join("out").exists());
+
+        let body = tar_gz(&[
+            ("t/link", tar::EntryType::Symlink, "x402.toml"),
+            ("t/link", tar::EntryType::Regular, "overwritten"),
+        ]);
+        let (_, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+        assert!(matches!(violation(result), ArchiveViolation::UnsafePath { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_extract_checks_links_from_template_root() {
+        // `t/` is unwrapped, so `t/up -> ..` would point above the template
+        let body = tar_gz(&[
+            ("t/x402.toml", tar::EntryType::Regular, "[template]"),
+            ("t/up", tar::EntryType::Symlink, ".."),
+        ]);
+        let (dest, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+        assert_eq!(
+            violation(result),
+            ArchiveViolation::UnsafeLink {
+                entry: "t/up".to_string(),
+                target: "..".to_string()
+            }
+        );
+        assert!(!dest.path().join("out").exists());
+
+        // Without a wrapper directory the archive root is the template root
+        let body = tar_gz(&[
+            ("x402.toml", tar::EntryType::Regular, "[template]"),
+            ("src/up", tar::EntryType::Symlink, ".."),
+        ]);
+        let (dest, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+        result.unwrap();
+        #[cfg(unix)]
+        assert_eq!(
+            std::fs::read_link(dest.path().join("out/src/up")).unwrap(),
+            Path::new("..")
+        );
+
+        // Hard links must name a file extracted earlier
+        let body = tar_gz(&[
+            ("t/src/main.rs", tar::EntryType::Regular, "fn main() {}"),
+            ("t/copy", tar::EntryType::Link, "t/src"),
+        ]);
+        let (_, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+        assert!(matches!(violation(result), ArchiveViolation::UnsafeLink { .. }));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_extract_keeps_permissions() {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        writer.start_file("t/x402.toml", options.unix_permissions(0o644)).unwrap();
+        writer.write_all(b"[template]").unwrap();
+        writer.start_file("t/scripts/dev.sh", options.unix_permissions(0o4755)).unwrap();
+        writer.write_all(b"#!/bin/sh").unwrap();
+        let body = writer.finish().unwrap().into_inner();
+
+        let (dest, result) = extract_served(Downloader::new(), body, ArchiveFormat::Zip).await;
+        result.unwrap();
+        let mode = |path: &str| {
+            let metadata = std::fs::metadata(dest.path().join(path)).unwrap();
+            metadata.permissions().mode() & 0o7777
+        };
+        // setuid is dropped
+        assert_eq!(mode("out/scripts/dev.sh"), 0o755);
+        assert_eq!(mode("out/x402.toml"), 0o644);
+
+        let body = tar_gz(&[
+            ("t/run.sh", tar::EntryType::Regular, "#!/bin/sh"),
+            ("t/again.sh", tar::EntryType::Link, "t/run.sh"),
+        ]);
+        let (dest, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+        result.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dest.path().join("out/again.sh")).unwrap(),
+            "#!/bin/sh"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extract_enforces_limits() {
+        let limits = ExtractLimits {
+            max_entries: 2,
+            ..ExtractLimits::default()
+        };
+        let body = zip(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let downloader = Downloader::new().with_limits(limits);
+        let (_, result) = extract_served(downloader, body, ArchiveFormat::Zip).await;
+        assert_eq!(violation(result), ArchiveViolation::TooManyEntries { limit: 2 });
+
+        let body = tar_gz(&[
+            ("a", tar::EntryType::Regular, "1"),
+            ("b", tar::EntryType::Regular, "2"),
+            ("c", tar::EntryType::Regular, "3"),
+        ]);
+        let downloader = Downloader::new().with_limits(limits);
+        let (_, result) = extract_served(downloader, body, ArchiveFormat::TarGz).await;
+        assert_eq!(violation(result), ArchiveViolation::TooManyEntries { limit: 2 });
+
+        let limits = ExtractLimits {
+            max_total_size: 1000,
+            ..ExtractLimits::default()
+        };
+        let big = "x".repeat(600);
+        let body = zip(&[("a", &big), ("b", &big)]);
+        let downloader = Downloader::new().with_limits(limits);
+        let (_, result) = extract_served(downloader, body, ArchiveFormat::Zip).await;
+        assert_eq!(violation(result), ArchiveViolation::TooLarge { limit: 1000 });
+    }
+
+    #[tokio::test]
+    async fn test_extract_rejects_zip_bomb() {
+        let zeros = "\0".repeat(16 * 1024 * 1024);
+        let body = zip(&[("t/x402.toml", "[template]"), ("t/bomb.bin", &zeros)]);
+        assert!(body.len() < 100 * 1024);
+
+        let (_, result) = extract_served(Downloader::new(), body, ArchiveFormat::Zip).await;
+        assert_eq!(
+            violation(result),
+            ArchiveViolation::CompressionRatio { limit: 100 }
+        );
+
+        let body = tar_gz(&[("t/bomb.bin", tar::EntryType::Regular, &zeros)]);
+        let (_, result) = extract_served(Downloader::new(), body, ArchiveFormat::TarGz).await;
+        assert!(matches!(
+            violation(result),
+            ArchiveViolation::CompressionRatio { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_download_size_limit() {
+        let body = zip(&[("t/x402.toml", &"#".repeat(4096))]);
+        let limit = body.len() as u64 - 1;
+        let (_, result) = extract_served(
+            Downloader::new().with_max_download_size(limit),
+            body.clone(),
+            ArchiveFormat::Zip,
+        )
+        .await;
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("larger than the"), "{}", message);
+
+        // Without a Content-Length the limit applies while streaming
+        let mut server = mockito::Server::new_async().await;
+        let chunks = body.clone();
+        server
+            .mock("GET", "/template")
+            .with_chunked_body(move |w| w.write_all(&chunks))
+            .create_async()
+            .await;
+        let dest = tempfile::TempDir::new().unwrap();
+        let file = dest.path().join("archive.zip");
+        let url = format!("{}/template", server.url());
+        let downloader = Downloader::new().with_max_download_size(limit);
+        let request = downloader.client.get(&url);
+        let result = downloader
+            .fetch_into(request, &mut std::fs::File::create(&file).unwrap())
+            .await;
+        assert!(result.unwrap_err().to_string().contains("larger than the"));
+
+        let (dest, result) = extract_served(
+            Downloader::new().with_max_download_size(limit + 1),
+            body,
+            ArchiveFormat::Zip,
+        )
+        .await;
+        result.unwrap();
+        assert!(dest.path().join("out/x402.toml").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_download_stall_timeout() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/template")
+            .with_chunked_body(|w| {
+                w.write_all(b"PK")?;
+                std::thread::sleep(Duration::from_millis(500));
+                w.write_all(b"\x03\x04")
+            })
+            .create_async()
+            .await;
+
+        let dest = tempfile::TempDir::new().unwrap();
+        let url = format!("{}/template", server.url());
+        let result = Downloader::new()
+            .with_stall_timeout(Duration::from_millis(100))
+            .download_archive(&url, ArchiveFormat::Zip, dest.path())
+            .await;
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("Download stalled"), "{}", message);
     }
 
     #[test]
-    fn test_github_to_zipball_url() {
-        let result =
-            Downloader::github_to_zipball_url("https://github.com/user/my-repo").unwrap();
-        assert!(result.contains("user/my-repo"));
-        assert!(result.contains("archive"));
-        assert!(result.contains(".zip"));
+    fn test_stays_inside() {
+        assert!(stays_inside(Path::new("t/docs"), Path::new("../x402.toml")));
+        assert!(stays_inside(Path::new("t"), Path::new("./src/../Cargo.toml")));
+        assert!(!stays_inside(Path::new("t"), Path::new("../../etc/passwd")));
+        assert!(!stays_inside(Path::new(""), Path::new("/etc/passwd")));
     }
 }
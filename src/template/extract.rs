@@ -0,0 +1,399 @@
+//! Turning a working project into a template (`cargo-x402 template extract`)
+//!
+//! Chosen literal values (crate name, author, port, payTo address, ...) are
+//! replaced with Liquid variables in a copy of the project. Liquid markup
+//! the project already contains is escaped, so rendering the copy with the
+//! original values as parameters gives back the original files.
+
+use super::filter::FileFilter;
+use super::render::Renderer;
+use crate::error::{Error, Result};
+use crate::schema::ParameterValues;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A literal value found in a project that could become a variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// Variable name it becomes
+    pub variable: String,
+    /// The value as it appears in the project
+    pub literal: String,
+    /// What the value is, for prompts
+    pub label: &'static str,
+    /// Pattern the parameter's values must match, if any
+    pub pattern: Option<&'static str>,
+}
+
+/// One literal to replace, and the Liquid expression replacing it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    /// Text to look for
+    pub literal: String,
+    /// Liquid output tag to put in its place
+    pub expression: String,
+}
+
+impl Replacement {
+    /// Replace `literal` with `{{ variable }}`, plus its snake_case form
+    /// (`my_app` for `my-app`, as used in Rust paths) when that differs
+    pub fn for_variable(variable: &str, literal: &str) -> Vec<Self> {
+        let mut replacements = vec![Self {
+            literal: literal.to_string(),
+            expression: format!("{{{{ {} }}}}", variable),
+        }];
+        let snake = literal.replace('-', "_");
+        if snake != literal {
+            replacements.push(Self {
+                literal: snake,
+                expression: format!("{{{{ {} | replace: \"-\", \"_\" }}}}", variable),
+            });
+        }
+        replacements
+    }
+}
+
+/// Outcome of extracting a template
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    /// Files copied, relative to the project root
+    pub files: Vec<PathBuf>,
+
+    /// Files that had at least one literal replaced
+    pub templated: Vec<PathBuf>,
+
+    /// Number of replacements made for each literal
+    pub replaced: BTreeMap<String, usize>,
+}
+
+/// Finds and replaces literals in a project
+pub struct Extractor;
+
+impl Extractor {
+    /// Guess values worth turning into variables: the crate name and first
+    /// author from Cargo.toml, a server port and a payTo address
+    pub fn detect(project: &Path) -> Result<Vec<Candidate>> {
+        let mut candidates = Vec::new();
+
+        if let Ok(content) = std::fs::read_to_string(project.join("Cargo.toml")) {
+            let manifest: toml::Value = toml::from_str(&content)
+                .map_err(|e| Error::TomlError(format!("Invalid Cargo.toml: {}", e)))?;
+            let package = manifest.get("package");
+            if let Some(name) = package.and_then(|p| p.get("name")).and_then(|n| n.as_str()) {
+                candidates.push(Candidate {
+                    variable: "project_name".to_string(),
+                    literal: name.to_string(),
+                    label: "crate name",
+                    pattern: None,
+                });
+            }
+            let author = package
+                .and_then(|p| p.get("authors"))
+                .and_then(|a| a.as_array())
+                .and_then(|a| a.first())
+                .and_then(|a| a.as_str());
+            if let Some(author) = author {
+                candidates.push(Candidate {
+                    variable: "author".to_string(),
+                    literal: author.to_string(),
+                    label: "author",
+                    pattern: None,
+                });
+            }
+        }
+
+        let texts = Self::text_files(project)?;
+        let port = Regex::new(
+            r#"(?i)(?:\bport\b["']?\s*[:=]\s*["']?|(?:localhost|127\.0\.0\.1|0\.0\.0\.0):)(\d{2,5})\b"#,
+        )
+        .expect("valid regex");
+        if let Some(port) = most_common(&texts, &port) {
+            candidates.push(Candidate {
+                variable: "port".to_string(),
+                literal: port,
+                label: "port",
+                pattern: Some("^[0-9]+$"),
+            });
+        }
+
+        let address = Regex::new(r"\b(0x[0-9a-fA-F]{40})\b").expect("valid regex");
+        let pay_to = Regex::new(r#"(?i)pay_?to["']?\s*[:=]\s*["']?(0x[0-9a-fA-F]{40})\b"#)
+            .expect("valid regex");
+        if let Some(address) = most_common(&texts, &pay_to).or_else(|| most_common(&texts, &address))
+        {
+            candidates.push(Candidate {
+                variable: "pay_to".to_string(),
+                literal: address,
+                label: "payTo address",
+                pattern: Some("^0x[0-9a-fA-F]{40}$"),
+            });
+        }
+
+        Ok(candidates)
+    }
+
+    /// Copy `project` into `output`, replacing literals in text files.
+    ///
+    /// Skips what a working copy would not commit (`.git`, `target/` and
+    /// anything `.gitignore`d) and any existing x402.toml.
+    pub fn extract(
+        project: &Path,
+        output: &Path,
+        replacements: &[Replacement],
+    ) -> Result<ExtractReport> {
+        let mut report = ExtractReport::default();
+        for rel_path in Self::project_files(project)? {
+            let src = project.join(&rel_path);
+            let dest = output.join(&rel_path);
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| Error::FileSystemError(format!("Cannot create dir: {}", e)))?;
+            }
+
+            let text = std::fs::read(&src)
+                .ok()
+                .filter(|bytes| !Renderer::is_binary(bytes))
+                .and_then(|bytes| String::from_utf8(bytes).ok());
+            match text {
+                Some(content) => {
+                    let (templated, counts) = templatize(&content, replacements);
+                    if !counts.is_empty() {
+                        report.templated.push(rel_path.clone());
+                    }
+                    for (literal, count) in counts {
+                        *report.replaced.entry(literal).or_default() += count;
+                    }
+                    std::fs::write(&dest, templated).map_err(|e| {
+                        Error::FileSystemError(format!("Cannot write {}: {}", dest.display(), e))
+                    })?;
+                    Renderer::copy_permissions(&src, &dest)?;
+                }
+                None => {
+                    std::fs::copy(&src, &dest).map_err(|e| {
+                        Error::FileSystemError(format!("Cannot copy {}: {}", src.display(), e))
+                    })?;
+                }
+            }
+            report.files.push(rel_path);
+        }
+        Ok(report)
+    }
+
+    /// Render the extracted template with `parameters` and list the files
+    /// that come out different from (or missing compared to) the project
+    pub fn verify(
+        project: &Path,
+        template: &Path,
+        parameters: &ParameterValues,
+    ) -> Result<Vec<PathBuf>> {
+        let scratch = tempfile::TempDir::new()
+            .map_err(|e| Error::FileSystemError(format!("Cannot create temp directory: {}", e)))?;
+        let schema = crate::schema::Validator::load_and_validate(&template.join("x402.toml"))?;
+        let report = Renderer::render(template, scratch.path(), parameters, &schema)?;
+
+        let mut mismatched = Vec::new();
+        for rel_path in Self::project_files(project)? {
+            let original = std::fs::read(project.join(&rel_path)).ok();
+            let rendered = std::fs::read(scratch.path().join(&rel_path)).ok();
+            if original != rendered {
+                mismatched.push(rel_path);
+            }
+        }
+        let expected = Self::project_files(project)?;
+        mismatched.extend(report.rendered.into_iter().filter(|p| !expected.contains(p)));
+        Ok(mismatched)
+    }
+
+    /// Files of the project that belong in the template, sorted
+    fn project_files(project: &Path) -> Result<Vec<PathBuf>> {
+        let filter = FileFilter::new(None)?.for_working_copy(project)?;
+        let mut files = Vec::new();
+        let mut walker = WalkDir::new(project).sort_by_file_name().into_iter();
+        while let Some(entry) = walker.next() {
+            let Ok(entry) = entry else { continue };
+            let Ok(rel_path) = entry.path().strip_prefix(project) else {
+                continue;
+            };
+            if rel_path.as_os_str().is_empty() {
+                continue;
+            }
+            if entry.file_type().is_dir() {
+                if entry.file_name() == ".git" || filter.check_dir(rel_path).is_some() {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+            if rel_path == Path::new("x402.toml") || filter.check_file(rel_path).is_some() {
+                continue;
+            }
+            files.push(rel_path.to_path_buf());
+        }
+        Ok(files)
+    }
+
+    fn text_files(project: &Path) -> Result<Vec<String>> {
+        Ok(Self::project_files(project)?
+            .into_iter()
+            .map(|rel_path| project.join(rel_path))
+            .filter_map(|path| std::fs::read(path).ok())
+            .filter(|bytes| !Renderer::is_binary(bytes))
+            .filter_map(|bytes| String::from_utf8(bytes).ok())
+            .collect())
+    }
+}
+
+/// The most frequent first capture of `pattern` across `texts`
+fn most_common(texts: &[String], pattern: &Regex) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for text in texts {
+        for value in pattern.captures_iter(text).filter_map(|c| c.get(1)) {
+            *counts.entry(value.as_str()).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(value, _)| value.to_string())
+}
+
+/// Escape Liquid markup already in `content` and replace whole-word
+/// occurrences of each literal, longest first. Returns the new content and
+/// how many times each literal was replaced.
+pub fn templatize(content: &str, replacements: &[Replacement]) -> (String, BTreeMap<String, usize>) {
+    let mut ordered: Vec<&Replacement> =
+        replacements.iter().filter(|r| !r.literal.is_empty()).collect();
+    ordered.sort_by_key(|r| std::cmp::Reverse(r.literal.len()));
+
+    let mut out = String::with_capacity(content.len());
+    let mut counts = BTreeMap::new();
+    let mut rest = content;
+    let mut previous: Option<char> = None;
+    'scan: while let Some(c) = rest.chars().next() {
+        for delimiter in ["{{", "{%"] {
+            if rest.starts_with(delimiter) {
+                out.push_str(&format!("{{{{ \"{}\" }}}}", delimiter));
+                rest = &rest[delimiter.len()..];
+                previous = Some('{');
+                continue 'scan;
+            }
+        }
+
+        for replacement in &ordered {
+            let literal = replacement.literal.as_str();
+            if !rest.starts_with(literal) {
+                continue;
+            }
+            let first = literal.chars().next().expect("non-empty literal");
+            let last = literal.chars().next_back().expect("non-empty literal");
+            let next = rest[literal.len()..].chars().next();
+            let starts_word = !(is_word(first) && previous.is_some_and(is_word));
+            let ends_word = !(is_word(last) && next.is_some_and(is_word));
+            if starts_word && ends_word {
+                out.push_str(&replacement.expression);
+                *counts.entry(literal.to_string()).or_default() += 1;
+                rest = &rest[literal.len()..];
+                previous = Some(last);
+                continue 'scan;
+            }
+        }
+
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+        previous = Some(c);
+    }
+    (out, counts)
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_templatize_replaces_whole_words_and_escapes_liquid() {
+        let mut replacements = Replacement::for_variable("project_name", "pay-api");
+        replacements.extend(Replacement::for_variable("port", "3000"));
+
+        let content = "name = \"pay-api\"\nuse pay_api::run;\nlisten(3000); // not 30000 or pay-api-core\n{{ raw }} {% tag %}\n";
+        let (out, counts) = templatize(content, &replacements);
+
+        assert_eq!(
+            out,
+            "name = \"{{ project_name }}\"\nuse {{ project_name | replace: \"-\", \"_\" }}::run;\n\
+             listen({{ port }}); // not 30000 or pay-api-core\n\
+             {{ \"{{\" }} raw }} {{ \"{%\" }} tag %}\n"
+        );
+        assert_eq!(counts["pay-api"], 1);
+        assert_eq!(counts["pay_api"], 1);
+        assert_eq!(counts["3000"], 1);
+    }
+
+    #[test]
+    fn test_detect_and_round_trip() {
+        let project = TempDir::new().unwrap();
+        let root = project.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"pay-api\"\nversion = \"0.1.0\"\nauthors = [\"Jo <jo@example.com>\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/main.rs"),
+            "// {{ not a variable }}\nconst PAY_TO: &str = \"0x1111111111111111111111111111111111111111\";\n\
+             fn main() { pay_api::serve(\"0.0.0.0:8080\"); }\n",
+        )
+        .unwrap();
+        std::fs::write(root.join(".env.example"), "PORT=8080\n").unwrap();
+        std::fs::write(root.join("target/debug/app"), "binary").unwrap();
+
+        let candidates = Extractor::detect(root).unwrap();
+        let found: Vec<(&str, &str)> = candidates
+            .iter()
+            .map(|c| (c.variable.as_str(), c.literal.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("project_name", "pay-api"),
+                ("author", "Jo <jo@example.com>"),
+                ("port", "8080"),
+                ("pay_to", "0x1111111111111111111111111111111111111111"),
+            ]
+        );
+
+        let replacements: Vec<Replacement> = candidates
+            .iter()
+            .flat_map(|c| Replacement::for_variable(&c.variable, &c.literal))
+            .collect();
+        let output = TempDir::new().unwrap();
+        let report = Extractor::extract(root, output.path(), &replacements).unwrap();
+        assert_eq!(report.files.len(), 3);
+        assert!(!output.path().join("target").exists());
+        let main = std::fs::read_to_string(output.path().join("src/main.rs")).unwrap();
+        assert!(main.contains("\"{{ pay_to }}\""));
+
+        std::fs::write(
+            output.path().join("x402.toml"),
+            "[template]\nname = \"t\"\ndescription = \"extracted template\"\nversion = \"0.1.0\"\n\
+             authors = [\"Jo\"]\nrepository = \"https://github.com/jo/t\"\n",
+        )
+        .unwrap();
+        let mut params = ParameterValues::new();
+        for c in &candidates {
+            params.insert(c.variable.clone(), c.literal.clone().into());
+        }
+        assert!(Extractor::verify(root, output.path(), &params).unwrap().is_empty());
+
+        params.insert("port".to_string(), "9999".into());
+        let mismatched = Extractor::verify(root, output.path(), &params).unwrap();
+        assert_eq!(mismatched, vec![PathBuf::from(".env.example"), PathBuf::from("src/main.rs")]);
+    }
+}
@@ -0,0 +1,290 @@
+//! File inclusion/exclusion rules applied during rendering
+
+use crate::error::{Error, Result};
+use crate::schema::template::FileRules;
+use glob::{MatchOptions, Pattern};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Exclude patterns applied when a template does not declare its own
+pub const DEFAULT_EXCLUDES: &[&str] = &["target/**", ".git/**", "*.log"];
+
+/// Build output that is never copied from a local working copy
+const WORKING_COPY_EXCLUDE: &str = "target/**";
+
+/// Why a template file was left out of the rendered project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The file matched none of the `[files] include` patterns
+    NotIncluded,
+
+    /// The file (or one of its parent directories) matched an exclude pattern
+    Excluded(String),
+
+    /// The file is ignored by a `.gitignore` in a local template
+    Ignored,
+
+    /// Its templated name (or a parent directory's) rendered to nothing
+    EmptyName,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::NotIncluded => write!(f, "not matched by any include pattern"),
+            SkipReason::Excluded(pattern) => write!(f, "excluded by '{}'", pattern),
+            SkipReason::Ignored => write!(f, "ignored by .gitignore"),
+            SkipReason::EmptyName => write!(f, "name rendered empty"),
+        }
+    }
+}
+
+/// Compiled `[files]` rules from a template's x402.toml
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    raw: Vec<Pattern>,
+    /// Paths not ignored by `.gitignore`, when reading a working copy
+    visible: Option<HashSet<PathBuf>>,
+}
+
+impl FileFilter {
+    /// Compile file rules, falling back to [`DEFAULT_EXCLUDES`] when no
+    /// exclude patterns are declared
+    pub fn new(rules: Option<&FileRules>) -> Result<Self> {
+        let (include, exclude, raw) = match rules {
+            Some(rules) => (rules.include.clone(), rules.exclude.clone(), rules.raw.clone()),
+            None => (Vec::new(), Vec::new(), Vec::new()),
+        };
+
+        let exclude = if exclude.is_empty() {
+            DEFAULT_EXCLUDES.iter().map(|p| p.to_string()).collect()
+        } else {
+            exclude
+        };
+
+        Ok(Self {
+            include: Self::compile(&include, "include")?,
+            exclude: Self::compile(&exclude, "exclude")?,
+            raw: Self::compile(&raw, "raw")?,
+            visible: None,
+        })
+    }
+
+    /// Additionally skip what a local working copy would not commit:
+    /// anything matched by its `.gitignore` files, and `target/`
+    pub fn for_working_copy(mut self, root: &Path) -> Result<Self> {
+        if !self.exclude.iter().any(|p| p.as_str() == WORKING_COPY_EXCLUDE) {
+            self.exclude.push(Pattern::new(WORKING_COPY_EXCLUDE).expect("valid pattern"));
+        }
+
+        let mut visible = HashSet::new();
+        let walker = ignore::WalkBuilder::new(root)
+            .hidden(false)
+            .parents(false)
+            .ignore(false)
+            .git_global(false)
+            .git_exclude(false)
+            .require_git(false)
+            .build();
+        for entry in walker {
+            let entry = entry.map_err(|e| {
+                Error::FileSystemError(format!("Cannot read template directory: {}", e))
+            })?;
+            if let Ok(rel_path) = entry.path().strip_prefix(root) {
+                visible.insert(rel_path.to_path_buf());
+            }
+        }
+
+        self.visible = Some(visible);
+        Ok(self)
+    }
+
+    fn compile(patterns: &[String], context: &str) -> Result<Vec<Pattern>> {
+        patterns
+            .iter()
+            .map(|p| {
+                Pattern::new(p).map_err(|e| Error::ValidationError {
+                    field: format!("files.{}", context),
+                    message: format!("Invalid glob pattern '{}': {}", p, e),
+                })
+            })
+            .collect()
+    }
+
+    /// Check whether a directory should be skipped entirely
+    ///
+    /// Only exclude patterns (and `.gitignore` for working copies) can prune
+    /// a directory, since include patterns may still match files further
+    /// down the tree.
+    pub fn check_dir(&self, rel_path: &Path) -> Option<SkipReason> {
+        let path = to_slash(rel_path);
+        let with_slash = format!("{}/", path);
+
+        self.exclude
+            .iter()
+            .find(|p| matches(p, &path) || matches(p, &with_slash))
+            .map(|p| SkipReason::Excluded(p.as_str().to_string()))
+            .or_else(|| self.check_ignored(rel_path))
+    }
+
+    /// Check whether a file should be skipped
+    ///
+    /// Include patterns are applied first, then exclude patterns filter the
+    /// result. A pattern matching any parent directory counts as a match.
+    pub fn check_file(&self, rel_path: &Path) -> Option<SkipReason> {
+        if !self.include.is_empty()
+            && !self.include.iter().any(|p| matches_self_or_ancestor(p, rel_path))
+        {
+            return Some(SkipReason::NotIncluded);
+        }
+
+        self.exclude
+            .iter()
+            .find(|p| matches_self_or_ancestor(p, rel_path))
+            .map(|p| SkipReason::Excluded(p.as_str().to_string()))
+            .or_else(|| self.check_ignored(rel_path))
+    }
+
+    /// Whether a file is listed in `[files] raw`, to be copied without
+    /// Liquid rendering
+    pub fn is_raw(&self, rel_path: &Path) -> bool {
+        self.raw.iter().any(|p| matches_self_or_ancestor(p, rel_path))
+    }
+
+    fn check_ignored(&self, rel_path: &Path) -> Option<SkipReason> {
+        match &self.visible {
+            Some(visible) if !visible.contains(rel_path) => Some(SkipReason::Ignored),
+            _ => None,
+        }
+    }
+}
+
+/// Check a `[files]` pattern against a path the way the rules do: a match on
+/// any parent directory counts
+pub fn pattern_matches(pattern: &Pattern, rel_path: &Path) -> bool {
+    matches_self_or_ancestor(pattern, rel_path)
+}
+
+/// Join path components with `/` so patterns behave the same on every platform
+fn to_slash(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn matches(pattern: &Pattern, path: &str) -> bool {
+    pattern.matches_with(path, MatchOptions::new())
+}
+
+fn matches_self_or_ancestor(pattern: &Pattern, rel_path: &Path) -> bool {
+    rel_path
+        .ancestors()
+        .filter(|a| !a.as_os_str().is_empty())
+        .any(|a| matches(pattern, &to_slash(a)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(include: &[&str], exclude: &[&str]) -> FileRules {
+        FileRules {
+            include: include.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+            raw: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_default_excludes() {
+        let filter = FileFilter::new(None).unwrap();
+
+        assert!(filter.check_file(Path::new("src/main.rs")).is_none());
+        assert_eq!(
+            filter.check_file(Path::new("debug.log")),
+            Some(SkipReason::Excluded("*.log".to_string()))
+        );
+        assert_eq!(
+            filter.check_dir(Path::new("target")),
+            Some(SkipReason::Excluded("target/**".to_string()))
+        );
+        assert!(filter.check_dir(Path::new("src")).is_none());
+    }
+
+    #[test]
+    fn test_include_then_exclude() {
+        let rules = rules(&["src/**/*", "Cargo.toml"], &["src/generated/**"]);
+        let filter = FileFilter::new(Some(&rules)).unwrap();
+
+        assert!(filter.check_file(Path::new("Cargo.toml")).is_none());
+        assert!(filter.check_file(Path::new("src/lib.rs")).is_none());
+        assert_eq!(
+            filter.check_file(Path::new("README.md")),
+            Some(SkipReason::NotIncluded)
+        );
+        assert_eq!(
+            filter.check_file(Path::new("src/generated/api.rs")),
+            Some(SkipReason::Excluded("src/generated/**".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_literal_directory_pattern_matches_contents() {
+        let rules = rules(&[], &["node_modules"]);
+        let filter = FileFilter::new(Some(&rules)).unwrap();
+
+        assert!(filter.check_file(Path::new("node_modules/pkg/index.js")).is_some());
+        assert!(filter.check_dir(Path::new("node_modules")).is_some());
+        // Declaring excludes replaces the defaults
+        assert!(filter.check_file(Path::new("app.log")).is_none());
+    }
+
+    #[test]
+    fn test_raw_patterns() {
+        let rules = FileRules {
+            raw: vec![".github/workflows".to_string(), "*.hbs".to_string()],
+            ..FileRules::default()
+        };
+        let filter = FileFilter::new(Some(&rules)).unwrap();
+
+        assert!(filter.is_raw(Path::new(".github/workflows/ci.yml")));
+        assert!(filter.is_raw(Path::new("views/page.hbs")));
+        assert!(!filter.is_raw(Path::new("src/main.rs")));
+        // Raw files are still rendered into the project
+        assert!(filter.check_file(Path::new("page.hbs")).is_none());
+    }
+
+    #[test]
+    fn test_working_copy_respects_gitignore_and_target() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::create_dir_all(root.join("secrets")).unwrap();
+        std::fs::write(root.join(".gitignore"), "secrets/\n.env\n").unwrap();
+        std::fs::write(root.join(".env"), "KEY=1").unwrap();
+        std::fs::write(root.join("secrets/key.pem"), "pem").unwrap();
+        std::fs::write(root.join("main.rs"), "").unwrap();
+
+        let rules = rules(&[], &["*.bak"]);
+        let filter = FileFilter::new(Some(&rules)).unwrap().for_working_copy(root).unwrap();
+
+        assert!(filter.check_file(Path::new("main.rs")).is_none());
+        assert!(filter.check_file(Path::new(".gitignore")).is_none());
+        assert_eq!(filter.check_file(Path::new(".env")), Some(SkipReason::Ignored));
+        assert_eq!(filter.check_dir(Path::new("secrets")), Some(SkipReason::Ignored));
+        assert_eq!(
+            filter.check_dir(Path::new("target")),
+            Some(SkipReason::Excluded("target/**".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let rules = rules(&["[unclosed"], &[]);
+        assert!(FileFilter::new(Some(&rules)).is_err());
+    }
+}
@@ -0,0 +1,591 @@
+//! Checks for template authors, beyond what `create` needs to succeed
+//!
+//! [`Linter::lint`] validates the manifest, then looks at every file that
+//! would be rendered:
+//!
+//! - Liquid syntax errors, with file and line
+//! - the same for templated file and directory names
+//! - variables that are neither declared parameters nor built-ins
+//! - declared parameters that no file uses
+//! - `[files]` patterns that match nothing
+
+use super::filter::{pattern_matches, FileFilter};
+use super::render::{Renderer, BUILTIN_VARIABLES};
+use crate::error::Result;
+use crate::schema::{TemplateSchema, Validator};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+const MANIFEST_FILE: &str = "x402.toml";
+
+/// Variables Liquid defines inside loops
+const LOOP_VARIABLES: &[&str] = &["forloop", "tablerowloop"];
+
+/// Words that can appear in expressions without being variables
+const KEYWORDS: &[&str] = &[
+    "and", "or", "contains", "true", "false", "nil", "null", "empty", "blank", "in", "reversed",
+    "with", "as", "else",
+];
+
+/// How serious a lint finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The template is broken: `create` would fail or render wrongly
+    Error,
+    /// Probably a mistake, but the template still works
+    Warning,
+}
+
+/// A single lint finding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// How serious the finding is
+    pub severity: Severity,
+    /// File the finding is about, relative to the template root
+    pub file: PathBuf,
+    /// 1-based line, when known
+    pub line: Option<usize>,
+    /// What is wrong
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Everything [`Linter::lint`] found
+#[derive(Debug, Default)]
+pub struct LintReport {
+    /// The manifest, when it could be parsed
+    #[allow(dead_code)] // Intentionally public for library users
+    pub schema: Option<TemplateSchema>,
+    /// Number of template files checked
+    pub files_checked: usize,
+    /// Findings, in file order
+    pub issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    /// Findings that make the template unusable
+    pub fn errors(&self) -> impl Iterator<Item = &LintIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    /// Findings that are probably mistakes
+    pub fn warnings(&self) -> impl Iterator<Item = &LintIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning)
+    }
+
+    /// Whether any finding is an error
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    fn push(&mut self, severity: Severity, file: &Path, line: Option<usize>, message: String) {
+        self.issues.push(LintIssue {
+            severity,
+            file: file.to_path_buf(),
+            line,
+            message,
+        });
+    }
+}
+
+/// Lints a template directory
+pub struct Linter;
+
+impl Linter {
+    /// Check the template in `template_dir`
+    pub fn lint(template_dir: &Path) -> Result<LintReport> {
+        let mut report = LintReport::default();
+        let manifest = Path::new(MANIFEST_FILE);
+
+        let schema = match std::fs::read_to_string(template_dir.join(manifest)) {
+            Ok(content) => match Validator::parse(&content) {
+                Ok(schema) => Some(schema),
+                Err(e) => {
+                    report.push(Severity::Error, manifest, None, e.to_string());
+                    None
+                }
+            },
+            Err(e) => {
+                report.push(Severity::Error, manifest, None, format!("Cannot read: {}", e));
+                None
+            }
+        };
+        if let Some(schema) = &schema {
+            if let Err(e) = Validator::validate_schema(schema) {
+                report.push(Severity::Error, manifest, None, e.to_string());
+            }
+        }
+
+        let files = Self::template_files(template_dir)?;
+        let filter = FileFilter::new(schema.as_ref().and_then(|s| s.files.as_ref()))
+            .and_then(|filter| filter.for_working_copy(template_dir));
+
+        // Free variables of each rendered file (or templated name), by name,
+        // with the first line used; no line means the variable is in the name
+        let mut used: Vec<(PathBuf, BTreeMap<String, Option<usize>>)> = Vec::new();
+        let mut names_checked = HashSet::new();
+        for rel_path in &files {
+            if rel_path == manifest {
+                continue;
+            }
+            if let Ok(filter) = &filter {
+                if filter.check_file(rel_path).is_some() {
+                    continue;
+                }
+            }
+
+            // Names are rendered for every file, binary or not
+            let mut prefix = PathBuf::new();
+            for component in rel_path.iter() {
+                prefix.push(component);
+                let name = component.to_string_lossy();
+                if !(name.contains("{{") || name.contains("{%"))
+                    || !names_checked.insert(prefix.clone())
+                {
+                    continue;
+                }
+                match check_syntax(&name) {
+                    Ok(()) => used.push((
+                        prefix.clone(),
+                        free_variables(&name).into_keys().map(|v| (v, None)).collect(),
+                    )),
+                    Err((_, message)) => {
+                        report.push(Severity::Error, &prefix, None, format!("{} (in its name)", message))
+                    }
+                }
+            }
+
+            // Raw and binary files are copied verbatim, so there is no Liquid to check
+            if filter.as_ref().is_ok_and(|filter| filter.is_raw(rel_path)) {
+                continue;
+            }
+            let Ok(bytes) = std::fs::read(template_dir.join(rel_path)) else {
+                continue;
+            };
+            if Renderer::is_binary(&bytes) {
+                continue;
+            }
+            let content = String::from_utf8_lossy(&bytes);
+            report.files_checked += 1;
+
+            if let Err((line, message)) = check_syntax(&content) {
+                report.push(Severity::Error, rel_path, line, message);
+                continue;
+            }
+            let variables = free_variables(&content)
+                .into_iter()
+                .map(|(name, line)| (name, Some(line)))
+                .collect();
+            used.push((rel_path.clone(), variables));
+        }
+
+        if let Some(schema) = &schema {
+            let declared: HashSet<&str> = schema
+                .parameters
+                .iter()
+                .flatten()
+                .map(|(name, _)| name.as_str())
+                .collect();
+
+            for (file, variables) in &used {
+                for (name, line) in variables {
+                    if !declared.contains(name.as_str())
+                        && !BUILTIN_VARIABLES.contains(&name.as_str())
+                    {
+                        let place = if line.is_some() { "" } else { " (in its name)" };
+                        report.push(
+                            Severity::Error,
+                            file,
+                            *line,
+                            format!(
+                                "'{}' is not a declared parameter or built-in variable{}",
+                                name, place
+                            ),
+                        );
+                    }
+                }
+            }
+
+            let referenced: HashSet<&str> = used
+                .iter()
+                .flat_map(|(_, variables)| variables.keys().map(String::as_str))
+                .collect();
+            let unused: BTreeSet<&str> = declared.difference(&referenced).copied().collect();
+            for name in unused {
+                report.push(
+                    Severity::Warning,
+                    manifest,
+                    None,
+                    format!("Parameter '{}' is never used", name),
+                );
+            }
+
+            if let Some(rules) = &schema.files {
+                let patterns = rules
+                    .include
+                    .iter()
+                    .map(|p| ("include", p))
+                    .chain(rules.exclude.iter().map(|p| ("exclude", p)))
+                    .chain(rules.raw.iter().map(|p| ("raw", p)));
+                for (kind, pattern) in patterns {
+                    // Invalid patterns were already reported by the validator
+                    let Ok(compiled) = glob::Pattern::new(pattern) else {
+                        continue;
+                    };
+                    if !files.iter().any(|f| pattern_matches(&compiled, f)) {
+                        report.push(
+                            Severity::Warning,
+                            manifest,
+                            None,
+                            format!("[files] {} pattern '{}' matches no files", kind, pattern),
+                        );
+                    }
+                }
+            }
+        }
+
+        report.schema = schema;
+        Ok(report)
+    }
+
+    /// Every file in the template, relative to its root, skipping `.git`
+    fn template_files(template_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let walker = WalkDir::new(template_dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| e.file_name() != ".git");
+        for entry in walker.filter_map(|e| e.ok()) {
+            if entry.file_type().is_file() {
+                if let Ok(rel_path) = entry.path().strip_prefix(template_dir) {
+                    files.push(rel_path.to_path_buf());
+                }
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Parse with Liquid, returning the line and message of a syntax error
+fn check_syntax(content: &str) -> std::result::Result<(), (Option<usize>, String)> {
+    let parser = liquid::ParserBuilder::with_stdlib()
+        .build()
+        .map_err(|e| (None, format!("Failed to build parser: {}", e)))?;
+
+    parser.parse(content).map(|_| ()).map_err(|e| {
+        let error = e.to_string();
+        // Errors look like "liquid:  --> 3:4\n  |\n ... \n  = Unknown tag."
+        static LOCATION: OnceLock<Regex> = OnceLock::new();
+        let location = LOCATION.get_or_init(|| Regex::new(r"-->\s*(\d+):\d+").expect("valid regex"));
+        let line = location
+            .captures(&error)
+            .and_then(|c| c[1].parse().ok());
+        let message = error
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("= "))
+            .unwrap_or(error.trim())
+            .to_string();
+        (line, format!("Liquid syntax error: {}", message))
+    })
+}
+
+/// Variables a template reads without defining them itself, with the line
+/// each is first used on
+///
+/// Liquid does not expose its syntax tree, so this scans the markup: it
+/// takes the root name of every variable path in `{{ }}` output and the
+/// expressions of `if`/`unless`/`case`/`for`/`assign`, and leaves out
+/// names bound by `assign`, `capture`, `for` and the like.
+fn free_variables(content: &str) -> BTreeMap<String, usize> {
+    static MARKUP: OnceLock<Regex> = OnceLock::new();
+    let markup = MARKUP.get_or_init(|| {
+        Regex::new(r"(?s)\{\{-?(.*?)-?\}\}|\{%-?(.*?)-?%\}").expect("valid regex")
+    });
+
+    let mut bound: HashSet<String> = LOOP_VARIABLES.iter().map(|v| v.to_string()).collect();
+    let mut found: BTreeMap<String, usize> = BTreeMap::new();
+    let mut skip_until: Option<&str> = None;
+
+    for caps in markup.captures_iter(content) {
+        let start = caps.get(0).map_or(0, |m| m.start());
+        let line = content[..start].matches('\n').count() + 1;
+        let mut use_expr = |expr: &str| {
+            for name in expression_variables(expr) {
+                found.entry(name).or_insert(line);
+            }
+        };
+
+        if let Some(output) = caps.get(1) {
+            if skip_until.is_none() {
+                use_expr(output.as_str());
+            }
+            continue;
+        }
+
+        let tag = caps.get(2).map_or("", |m| m.as_str()).trim();
+        let (name, rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        if let Some(end) = skip_until {
+            if name == end {
+                skip_until = None;
+            }
+            continue;
+        }
+
+        match name {
+            "raw" => skip_until = Some("endraw"),
+            "comment" => skip_until = Some("endcomment"),
+            "assign" => {
+                if let Some((variable, expr)) = rest.split_once('=') {
+                    bound.insert(variable.trim().to_string());
+                    use_expr(expr);
+                }
+            }
+            "capture" | "increment" | "decrement" => {
+                bound.insert(rest.trim().to_string());
+            }
+            "for" | "tablerow" => {
+                if let Some((variable, expr)) = rest.split_once(" in ") {
+                    bound.insert(variable.trim().to_string());
+                    use_expr(expr);
+                }
+            }
+            "if" | "elsif" | "unless" | "case" | "when" | "cycle" => use_expr(rest),
+            _ => {}
+        }
+    }
+
+    found.retain(|name, _| !bound.contains(name));
+    found
+}
+
+/// Root names of the variable paths in a Liquid expression
+fn expression_variables(expr: &str) -> Vec<String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut names = Vec::new();
+    let mut i = 0;
+    let mut after_dot = false;
+    let mut expect_filter = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' | '"' => {
+                // Skip string literals
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    i += 1;
+                }
+                i += 1;
+                after_dot = false;
+            }
+            '.' if chars.get(i + 1) == Some(&'.') => {
+                // Range operator, as in (1..n)
+                i += 2;
+                after_dot = false;
+            }
+            '.' => {
+                i += 1;
+                after_dot = true;
+            }
+            '|' => {
+                i += 1;
+                expect_filter = true;
+                after_dot = false;
+            }
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                after_dot = false;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '-')
+                {
+                    i += 1;
+                }
+                if chars.get(i) == Some(&'?') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let next = chars[i..].iter().find(|c| !c.is_whitespace());
+
+                let is_variable = !after_dot
+                    && !expect_filter
+                    && next != Some(&':')
+                    && !KEYWORDS.contains(&word.as_str());
+                if is_variable {
+                    names.push(word);
+                }
+                after_dot = false;
+                expect_filter = false;
+            }
+            c if c.is_whitespace() => i += 1,
+            _ => {
+                i += 1;
+                after_dot = false;
+            }
+        }
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(content: &str) -> Vec<String> {
+        free_variables(content).into_keys().collect()
+    }
+
+    #[test]
+    fn test_expression_variables() {
+        assert_eq!(expression_variables(" project_name "), vec!["project_name"]);
+        assert_eq!(
+            expression_variables(" db.url | default: fallback | upcase "),
+            vec!["db", "fallback"]
+        );
+        assert_eq!(
+            expression_variables(r#" db_type == "sqlite" and enable_docker "#),
+            vec!["db_type", "enable_docker"]
+        );
+        assert_eq!(expression_variables(" (1..count) "), vec!["count"]);
+        assert_eq!(expression_variables(" items[index] "), vec!["items", "index"]);
+        assert!(expression_variables(" 'literal' | append: \"x\" ").is_empty());
+    }
+
+    #[test]
+    fn test_free_variables_skip_bound_names() {
+        let content = "{% assign upper = name | upcase %}{{ upper }}\n\
+                       {% for feature in features limit: 2 %}{{ feature.name }}\
+                       {{ forloop.index }}{% endfor %}\n\
+                       {% capture title %}{{ heading }}{% endcapture %}{{ title }}\n\
+                       {% raw %}{{ not_a_variable }}{% endraw %}\n\
+                       {% comment %}{{ commented }}{% endcomment %}";
+
+        assert_eq!(names(content), vec!["features", "heading", "name"]);
+        assert_eq!(free_variables(content)["heading"], 3);
+    }
+
+    #[test]
+    fn test_check_syntax_reports_line() {
+        assert!(check_syntax("{% if x %}ok{% endif %}").is_ok());
+
+        let (line, message) = check_syntax("one\ntwo\n{% frob %}").unwrap_err();
+        assert_eq!(line, Some(3));
+        assert!(message.contains("Unknown tag"));
+    }
+
+    const MANIFEST: &str = r#"
+[template]
+name = "lint-test"
+description = "Template for lint tests"
+version = "1.0.0"
+authors = ["x402"]
+repository = "https://github.com/x402/lint-test"
+
+[parameters]
+enable_docker = { type = "boolean", default = true }
+unused_flag = { type = "boolean", default = false }
+
+[files]
+exclude = ["*.log", "docs/**"]
+raw = [".github/**", "*.hbs"]
+"#;
+
+    #[test]
+    fn test_lint_template() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("x402.toml"), MANIFEST).unwrap();
+        std::fs::write(root.join("build.log"), "{{ ignored }}").unwrap();
+        std::fs::write(
+            root.join("README.md"),
+            "# {{ project_name }}\n{% if enable_docker %}docker{% endif %}\n{{ typo_name }}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}\n{% endif %}\n").unwrap();
+        std::fs::create_dir_all(root.join(".github/workflows")).unwrap();
+        std::fs::write(root.join(".github/workflows/ci.yml"), "run: ${{ github.sha }}\n").unwrap();
+
+        let report = Linter::lint(root).unwrap();
+        let messages: Vec<String> = report.issues.iter().map(|i| i.to_string()).collect();
+
+        assert_eq!(report.files_checked, 2);
+        assert!(report.has_errors());
+        assert_eq!(report.errors().count(), 2, "{:?}", messages);
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("src/main.rs:2: Liquid syntax error")));
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("README.md:3: 'typo_name'")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("Parameter 'unused_flag' is never used")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("exclude pattern 'docs/**' matches no files")));
+        assert!(!messages.iter().any(|m| m.contains("'*.log'")));
+        assert!(messages
+            .iter()
+            .any(|m| m.contains("raw pattern '*.hbs' matches no files")));
+    }
+
+    #[test]
+    fn test_lint_checks_templated_names() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/{{ project_nmae }}")).unwrap();
+        std::fs::create_dir_all(root.join("{% if enable_docker %}docker{% endif %}")).unwrap();
+        std::fs::write(root.join("x402.toml"), MANIFEST).unwrap();
+        std::fs::write(root.join("src/{{ project_nmae }}/a.rs"), "").unwrap();
+        std::fs::write(root.join("src/{{ project_nmae }}/b.rs"), "").unwrap();
+        std::fs::write(root.join("{% if enable_docker %}docker{% endif %}/logo.png"), "").unwrap();
+        std::fs::write(root.join("{% if %}.txt"), "").unwrap();
+
+        let report = Linter::lint(root).unwrap();
+        let messages: Vec<String> = report.errors().map(|i| i.to_string()).collect();
+
+        assert_eq!(messages.len(), 2, "{:?}", messages);
+        assert!(messages.contains(
+            &"src/{{ project_nmae }}: 'project_nmae' is not a declared parameter or built-in \
+              variable (in its name)"
+                .to_string()
+        ));
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("{% if %}.txt: Liquid syntax error")));
+        // Used only in a directory name (of a binary file), but still used
+        assert!(!report
+            .warnings()
+            .any(|i| i.message.contains("'enable_docker'")));
+    }
+
+    #[test]
+    fn test_lint_missing_manifest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("README.md"), "{{ anything }}").unwrap();
+
+        let report = Linter::lint(dir.path()).unwrap();
+
+        assert_eq!(report.errors().count(), 1);
+        assert_eq!(report.issues[0].file, PathBuf::from("x402.toml"));
+    }
+}
@@ -0,0 +1,229 @@
+//! Combinations of a template's parameter values, for `cargo-x402 test`
+//!
+//! Every boolean takes both values and every enum each of its choices;
+//! strings keep their default. Combinations are numbered in mixed radix,
+//! with each parameter's default as digit 0, so combination 0 is "all
+//! defaults" and any combination can be built without enumerating the rest.
+
+use crate::schema::{Parameter, ParameterValue, ParameterValues};
+use std::collections::{BTreeSet, HashMap};
+
+/// One parameter's axis of the matrix
+#[derive(Debug, Clone)]
+struct Dimension {
+    name: String,
+    /// Values to try, default first
+    values: Vec<ParameterValue>,
+}
+
+/// The cartesian product of the boolean and enum parameters of a template
+#[derive(Debug, Clone)]
+pub struct ParameterMatrix {
+    dimensions: Vec<Dimension>,
+    /// Parameters that are not varied (strings), at their defaults
+    fixed: ParameterValues,
+}
+
+impl ParameterMatrix {
+    /// Build the matrix for a template's declared parameters
+    pub fn new(parameters: &HashMap<String, Parameter>) -> Self {
+        let mut names: Vec<&String> = parameters.keys().collect();
+        names.sort();
+
+        let mut dimensions = Vec::new();
+        let mut fixed = ParameterValues::new();
+        for name in names {
+            let param = &parameters[name];
+            let values = match param {
+                Parameter::Boolean { default, .. } => {
+                    vec![(*default).into(), (!*default).into()]
+                }
+                Parameter::Enum {
+                    choices, default, ..
+                } => std::iter::once(default)
+                    .chain(choices.iter().filter(|c| *c != default))
+                    .map(|c| ParameterValue::String(c.clone()))
+                    .collect(),
+                Parameter::String { .. } => {
+                    fixed.insert(name.clone(), param.default_value());
+                    continue;
+                }
+            };
+            dimensions.push(Dimension {
+                name: name.clone(),
+                values,
+            });
+        }
+
+        Self { dimensions, fixed }
+    }
+
+    /// Names of the varied parameters, in column order
+    pub fn names(&self) -> Vec<&str> {
+        self.dimensions.iter().map(|d| d.name.as_str()).collect()
+    }
+
+    /// Number of combinations (saturating for absurdly large matrices)
+    pub fn combinations(&self) -> u128 {
+        self.dimensions
+            .iter()
+            .fold(1u128, |n, d| n.saturating_mul(d.values.len().max(1) as u128))
+    }
+
+    /// Values of the varied parameters for a combination, in column order
+    pub fn values(&self, index: u128) -> Vec<&ParameterValue> {
+        let mut digits = Vec::with_capacity(self.dimensions.len());
+        let mut rest = index;
+        for dimension in self.dimensions.iter().rev() {
+            let radix = dimension.values.len().max(1) as u128;
+            digits.push(&dimension.values[(rest % radix) as usize]);
+            rest /= radix;
+        }
+        digits.reverse();
+        digits
+    }
+
+    /// Every parameter value for a combination, ready for rendering
+    pub fn combination(&self, index: u128) -> ParameterValues {
+        let mut values = self.fixed.clone();
+        for (dimension, value) in self.dimensions.iter().zip(self.values(index)) {
+            values.insert(dimension.name.clone(), value.clone());
+        }
+        values
+    }
+
+    /// Pick which combinations to run: all of them when there are at most
+    /// `count`, otherwise `count` chosen at random from `seed` (always
+    /// including the all-defaults combination 0), in ascending order
+    pub fn sample(&self, count: usize, seed: u64) -> Vec<u128> {
+        let total = self.combinations();
+        let count = count as u128;
+        if total <= count {
+            return (0..total).collect();
+        }
+        if count == 0 {
+            return Vec::new();
+        }
+
+        // Floyd's algorithm over 1..total, so 0 can be added unconditionally
+        let mut rng = SplitMix64(seed);
+        let others = total - 1;
+        let mut chosen = BTreeSet::new();
+        for j in (others - (count - 1))..others {
+            let pick = rng.below(j + 1);
+            if !chosen.insert(pick + 1) {
+                chosen.insert(j + 1);
+            }
+        }
+        chosen.insert(0);
+        chosen.into_iter().collect()
+    }
+}
+
+/// Small deterministic generator, so a sampled run can be repeated by seed
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`
+    fn below(&mut self, bound: u128) -> u128 {
+        let wide = ((self.next() as u128) << 64) | self.next() as u128;
+        wide % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters() -> HashMap<String, Parameter> {
+        let mut params = HashMap::new();
+        params.insert(
+            "enable_docker".to_string(),
+            Parameter::Boolean {
+                default: true,
+                description: None,
+            },
+        );
+        params.insert(
+            "database".to_string(),
+            Parameter::Enum {
+                choices: vec!["sqlite".to_string(), "postgres".to_string(), "none".to_string()],
+                default: "postgres".to_string(),
+                description: None,
+            },
+        );
+        params.insert(
+            "crate_name".to_string(),
+            Parameter::String {
+                default: "app".to_string(),
+                pattern: None,
+                description: None,
+            },
+        );
+        params
+    }
+
+    #[test]
+    fn test_matrix_enumerates_booleans_and_enums() {
+        let matrix = ParameterMatrix::new(&parameters());
+
+        assert_eq!(matrix.names(), vec!["database", "enable_docker"]);
+        assert_eq!(matrix.combinations(), 6);
+
+        // Combination 0 is all defaults
+        let defaults = matrix.combination(0);
+        assert_eq!(defaults["database"], "postgres".into());
+        assert_eq!(defaults["enable_docker"], true.into());
+        assert_eq!(defaults["crate_name"], "app".into());
+
+        let last = matrix.values(5);
+        assert_eq!(last, vec![&"none".into(), &false.into()]);
+
+        let all: BTreeSet<Vec<String>> = (0..matrix.combinations())
+            .map(|i| matrix.values(i).iter().map(|v| v.to_string()).collect())
+            .collect();
+        assert_eq!(all.len(), 6);
+    }
+
+    #[test]
+    fn test_matrix_without_parameters_has_one_combination() {
+        let matrix = ParameterMatrix::new(&HashMap::new());
+        assert_eq!(matrix.combinations(), 1);
+        assert_eq!(matrix.sample(10, 1), vec![0]);
+        assert!(matrix.combination(0).is_empty());
+    }
+
+    #[test]
+    fn test_sample_is_deterministic_and_includes_defaults() {
+        let mut params = HashMap::new();
+        for i in 0..10 {
+            params.insert(
+                format!("flag_{}", i),
+                Parameter::Boolean {
+                    default: false,
+                    description: None,
+                },
+            );
+        }
+        let matrix = ParameterMatrix::new(&params);
+        assert_eq!(matrix.combinations(), 1024);
+
+        let sample = matrix.sample(8, 42);
+        assert_eq!(sample.len(), 8);
+        assert_eq!(sample[0], 0);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert!(sample.iter().all(|i| *i < 1024));
+        assert_eq!(sample, matrix.sample(8, 42));
+        assert_ne!(sample, matrix.sample(8, 43));
+
+        assert_eq!(matrix.sample(2000, 42).len(), 1024);
+    }
+}
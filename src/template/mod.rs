@@ -5,15 +5,25 @@
 //!
 //! ## Workflow
 //!
-//! 1. **Download** (`downloader`): Clone template repository to temporary location
+//! 1. **Fetch** (`source`): Materialize the template from GitHub, a git remote,
+//!    an archive URL or a local directory
 //! 2. **Validate**: Parse and validate x402.toml manifest
-//! 3. **Render** (`render`): Process Liquid templates with user parameters
+//! 3. **Render** (`render`): Process Liquid templates with user parameters,
+//!    keeping only files allowed by the `[files]` rules (`filter`)
 //! 4. **Finalize**: Copy processed files to destination, cleanup .git directory
 //!
 //! ## Submodules
 //!
-//! - [`downloader`]: GitHub template repository cloning
+//! - [`diff`]: Line diffs of rendered files (`cargo-x402 create --dry-run --diff`)
+//! - [`downloader`]: GitHub zipball and archive URL downloads
+//! - [`extract`]: Turning a project into a template (`cargo-x402 template extract`)
+//! - [`filter`]: `[files]` include/exclude rules
+//! - [`lint`]: Checks for template authors (`cargo-x402 validate`)
+//! - [`matrix`]: Parameter combinations for `cargo-x402 test`
+//! - [`provenance`]: Recording which template revision a project came from
 //! - [`render`]: Liquid template rendering with parameter substitution
+//! - [`source`]: Pluggable template sources (GitHub, local, git, archive URLs)
+//! - [`store`]: Downloaded archives kept by commit, for re-use and offline mode
 //!
 //! ## Example
 //!
@@ -24,8 +34,22 @@
 //! // Template operations are coordinated through the commands module
 //! ```
 
+pub mod diff;
 pub mod downloader;
+pub mod extract;
+pub mod filter;
+pub mod lint;
+pub mod matrix;
+pub mod provenance;
 pub mod render;
+pub mod source;
+pub mod store;
 
 pub use downloader::Downloader;
-pub use render::Renderer;
+#[allow(unused_imports)] // Intentionally exported for library users
+pub use filter::{FileFilter, SkipReason};
+pub use provenance::Provenance;
+#[allow(unused_imports)] // Intentionally exported for library users
+pub use render::{PlannedFile, Preview, RenderReport, RenderedContent, Renderer};
+#[allow(unused_imports)] // Intentionally exported for library users
+pub use source::{Materialized, SourceRef, TemplateSource};
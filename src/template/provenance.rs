@@ -0,0 +1,79 @@
+//! Record of where a generated project's template came from
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File written into generated projects to record template provenance
+pub const PROVENANCE_FILE: &str = ".x402-template.toml";
+
+/// Where a template was fetched from, so a project can be regenerated
+/// from exactly the same template revision
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Template source (URL or path)
+    pub source: String,
+
+    /// Branch, tag or SHA the user asked for
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+
+    /// Resolved commit SHA, when the source is versioned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+
+    /// cargo-x402 version that generated the project
+    pub cli_version: String,
+}
+
+impl Provenance {
+    /// Create a provenance record for the running cargo-x402 version
+    pub fn new(
+        source: impl Into<String>,
+        reference: Option<String>,
+        commit: Option<String>,
+    ) -> Self {
+        Self {
+            source: source.into(),
+            reference,
+            commit,
+            cli_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// Write the record to [`PROVENANCE_FILE`] inside `project_dir`
+    pub fn write_to(&self, project_dir: &Path) -> Result<()> {
+        let body = toml::to_string(self)
+            .map_err(|e| Error::TomlError(format!("Cannot serialize provenance: {}", e)))?;
+        let content = format!(
+            "# Generated by cargo-x402: the template this project was created from\n{}",
+            body
+        );
+
+        std::fs::write(project_dir.join(PROVENANCE_FILE), content).map_err(|e| {
+            Error::FileSystemError(format!("Cannot write {}: {}", PROVENANCE_FILE, e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provenance_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let provenance = Provenance::new(
+            "https://github.com/user/repo",
+            Some("v1.2.0".to_string()),
+            Some("0123456789abcdef0123456789abcdef01234567".to_string()),
+        );
+
+        provenance.write_to(dir.path()).unwrap();
+
+        let content = std::fs::read_to_string(dir.path().join(PROVENANCE_FILE)).unwrap();
+        assert!(content.contains("ref = \"v1.2.0\""));
+        let parsed: Provenance = toml::from_str(&content).unwrap();
+        assert_eq!(parsed, provenance);
+    }
+}
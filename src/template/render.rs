@@ -1,79 +1,373 @@
 //! Template rendering with Liquid
 
+use super::downloader::{stays_inside, symlink};
+use super::filter::{FileFilter, SkipReason};
 use crate::error::{Error, Result};
+use crate::schema::{ParameterValue, ParameterValues, TemplateSchema};
 use liquid::model::Value;
 use std::collections::HashMap;
-use std::path::Path;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
 use walkdir::WalkDir;
 
+const MANIFEST_FILE: &str = "x402.toml";
+
+/// Bytes searched for a NUL when deciding whether a file is binary
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Variables `create` always passes to templates, whether or not they are
+/// declared as parameters
+pub const BUILTIN_VARIABLES: &[&str] = &["project_name", "author", "version", "date"];
+
+/// Values of the [`BUILTIN_VARIABLES`] for a project named `project_name`:
+/// the user's name, this tool's version and today's date
+pub fn builtin_values(project_name: &str) -> ParameterValues {
+    let mut values = ParameterValues::new();
+    values.insert("project_name".to_string(), project_name.into());
+    values.insert("author".to_string(), whoami::realname().into());
+    values.insert("version".to_string(), env!("CARGO_PKG_VERSION").into());
+    values.insert(
+        "date".to_string(),
+        chrono::Local::now().format("%Y-%m-%d").to_string().into(),
+    );
+    values
+}
+
+/// Outcome of rendering a template directory
+#[derive(Debug, Default)]
+pub struct RenderReport {
+    /// Files written to the output directory, relative to the template root
+    pub rendered: Vec<PathBuf>,
+
+    /// Those of `rendered` copied verbatim rather than through Liquid:
+    /// binary files, `[files] raw` matches and symlinks
+    pub copied: Vec<PathBuf>,
+
+    /// Files and directories left out by the `[files]` rules, with the reason
+    pub skipped: Vec<(PathBuf, SkipReason)>,
+}
+
+/// What a template file becomes in the project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderedContent {
+    /// Output of Liquid
+    Templated(String),
+
+    /// Copied byte for byte because the file is binary
+    Binary {
+        /// Size in bytes
+        size: u64,
+    },
+
+    /// Copied byte for byte because `[files] raw` lists it
+    Raw {
+        /// Size in bytes
+        size: u64,
+    },
+
+    /// Symlink recreated with this target, which stays inside the project
+    Link(PathBuf),
+}
+
+/// One file of a rendered template, worked out before anything is written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedFile {
+    /// Path in the template, relative to its root
+    pub source: PathBuf,
+
+    /// Path in the project, relative to its root, with names rendered
+    pub path: PathBuf,
+
+    /// What gets written there
+    pub content: RenderedContent,
+}
+
+impl PlannedFile {
+    /// Bytes the file takes in the project (a link counts as its target)
+    pub fn size(&self) -> u64 {
+        match &self.content {
+            RenderedContent::Templated(text) => text.len() as u64,
+            RenderedContent::Binary { size } | RenderedContent::Raw { size } => *size,
+            RenderedContent::Link(target) => target.as_os_str().len() as u64,
+        }
+    }
+}
+
+/// A template rendered in memory, for `create --dry-run`
+#[derive(Debug, Default)]
+pub struct Preview {
+    /// Files the project would contain, in template order
+    pub files: Vec<PlannedFile>,
+
+    /// Files and directories left out by the `[files]` rules, with the reason
+    pub skipped: Vec<(PathBuf, SkipReason)>,
+}
+
 /// Renders templates using Liquid templating engine
 pub struct Renderer;
 
 impl Renderer {
     /// Render all template files with provided parameters
+    ///
+    /// Files are filtered through the schema's `[files]` rules; `.git` and
+    /// the x402.toml manifest are never copied.
     pub fn render(
         template_path: &Path,
         output_path: &Path,
-        parameters: &HashMap<String, String>,
-    ) -> Result<()> {
+        parameters: &ParameterValues,
+        schema: &TemplateSchema,
+    ) -> Result<RenderReport> {
+        let filter = FileFilter::new(schema.files.as_ref())?;
+        Self::render_with_filter(template_path, output_path, parameters, &filter)
+    }
+
+    /// Render all template files that pass a prepared [`FileFilter`]
+    ///
+    /// File and directory names are rendered too, so `src/{{ project_name }}/`
+    /// works; a name that renders to nothing is left out along with
+    /// everything below it. Files keep their permissions, and symlinks are
+    /// recreated as long as they point inside the project.
+    pub fn render_with_filter(
+        template_path: &Path,
+        output_path: &Path,
+        parameters: &ParameterValues,
+        filter: &FileFilter,
+    ) -> Result<RenderReport> {
+        let mut report = RenderReport::default();
+
         // Ensure output directory exists
         std::fs::create_dir_all(output_path).map_err(|e| {
             Error::FileSystemError(format!("Cannot create output directory: {}", e))
         })?;
 
+        report.skipped = Self::walk(template_path, parameters, filter, |file| {
+            Self::write_file(template_path, output_path, &file)?;
+            if !matches!(file.content, RenderedContent::Templated(_)) {
+                report.copied.push(file.source.clone());
+            }
+            report.rendered.push(file.source);
+            Ok(())
+        })?;
+
+        Ok(report)
+    }
+
+    /// Render a template in memory, reporting what each file would become
+    /// without writing anything
+    pub fn preview(
+        template_path: &Path,
+        parameters: &ParameterValues,
+        filter: &FileFilter,
+    ) -> Result<Preview> {
+        let mut files = Vec::new();
+        let skipped = Self::walk(template_path, parameters, filter, |file| {
+            files.push(file);
+            Ok(())
+        })?;
+        Ok(Preview { files, skipped })
+    }
+
+    /// Work out each file of the template in turn and hand it to `visit`,
+    /// returning what the `[files]` rules and empty names left out
+    fn walk(
+        template_path: &Path,
+        parameters: &ParameterValues,
+        filter: &FileFilter,
+        mut visit: impl FnMut(PlannedFile) -> Result<()>,
+    ) -> Result<Vec<(PathBuf, SkipReason)>> {
+        let mut skipped = Vec::new();
+
+        // Rendered location of each directory walked so far, by template path
+        let mut dirs: HashMap<PathBuf, PathBuf> = HashMap::new();
+
         // Walk through template directory
-        for entry in WalkDir::new(template_path)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| {
-                e.path()
-                    .file_name()
-                    .map(|n| n != ".git" && n != "x402.toml")
-                    .unwrap_or(true)
-            })
-        {
+        let mut walker = WalkDir::new(template_path).sort_by_file_name().into_iter();
+        while let Some(entry) = walker.next() {
+            let Ok(entry) = entry else { continue };
             let rel_path = entry
                 .path()
                 .strip_prefix(template_path)
                 .map_err(|e| Error::FileSystemError(e.to_string()))?;
-            let dest_path = output_path.join(rel_path);
 
-            if entry.path().is_dir() {
-                std::fs::create_dir_all(&dest_path)
-                    .map_err(|e| Error::FileSystemError(format!("Cannot create dir: {}", e)))?;
+            if rel_path.as_os_str().is_empty() {
+                continue;
+            }
+
+            let is_dir = entry.file_type().is_dir();
+            if is_dir {
+                if entry.file_name() == ".git" {
+                    walker.skip_current_dir();
+                    continue;
+                } else if let Some(reason) = filter.check_dir(rel_path) {
+                    skipped.push((rel_path.to_path_buf(), reason));
+                    walker.skip_current_dir();
+                    continue;
+                }
+            } else if rel_path == Path::new(MANIFEST_FILE) {
+                continue;
+            } else if let Some(reason) = filter.check_file(rel_path) {
+                skipped.push((rel_path.to_path_buf(), reason));
+                continue;
+            }
+
+            let Some(name) = Self::render_name(entry.file_name(), rel_path, parameters)? else {
+                skipped.push((rel_path.to_path_buf(), SkipReason::EmptyName));
+                if is_dir {
+                    walker.skip_current_dir();
+                }
+                continue;
+            };
+            let parent = rel_path
+                .parent()
+                .and_then(|parent| dirs.get(parent))
+                .cloned()
+                .unwrap_or_default();
+            let dest_rel = parent.join(name);
+            if is_dir {
+                dirs.insert(rel_path.to_path_buf(), dest_rel);
+                continue;
+            }
+
+            let content = if entry.file_type().is_symlink() && cfg!(unix) {
+                RenderedContent::Link(Self::link_target(entry.path(), &dest_rel)?)
             } else {
-                Self::render_file(entry.path(), &dest_path, parameters)?;
+                Self::render_file(entry.path(), parameters, filter.is_raw(rel_path))?
+            };
+            visit(PlannedFile {
+                source: rel_path.to_path_buf(),
+                path: dest_rel,
+                content,
+            })?;
+        }
+
+        Ok(skipped)
+    }
+
+    /// Render a file or directory name, returning `None` when it renders to
+    /// nothing. The result may contain `/` to nest directories, but must
+    /// stay a relative path without `.`, `..` or empty names.
+    fn render_name(
+        name: &OsStr,
+        rel_path: &Path,
+        parameters: &ParameterValues,
+    ) -> Result<Option<PathBuf>> {
+        let Some(text) = name.to_str().filter(|n| n.contains("{{") || n.contains("{%")) else {
+            return Ok(Some(PathBuf::from(name)));
+        };
+
+        let rendered = Self::render_content(text, parameters).map_err(|e| {
+            Error::RenderError(format!("In the name of '{}': {}", rel_path.display(), e))
+        })?;
+        if rendered.is_empty() {
+            return Ok(None);
+        }
+
+        let invalid = |reason: &str| {
+            Err(Error::RenderError(format!(
+                "The name of '{}' renders to '{}', {}",
+                rel_path.display(),
+                rendered,
+                reason
+            )))
+        };
+        if rendered.contains('\\') {
+            return invalid("which contains a backslash");
+        }
+        let path = Path::new(&rendered);
+        if path
+            .components()
+            .any(|c| matches!(c, Component::RootDir | Component::Prefix(_)))
+        {
+            return invalid("which is an absolute path");
+        }
+        for part in rendered.split('/') {
+            match part {
+                ".." => return invalid("which leaves the project directory"),
+                "." => return invalid("which contains '.' as a name"),
+                p if p.trim().is_empty() => return invalid("which contains an empty name"),
+                _ => {}
             }
         }
 
-        Ok(())
+        Ok(Some(path.to_path_buf()))
     }
 
-    /// Render a single file
-    fn render_file(src: &Path, dest: &Path, parameters: &HashMap<String, String>) -> Result<()> {
-        // Skip binary files
-        if Self::is_binary_file(src) {
-            std::fs::copy(src, dest)
-                .map_err(|e| Error::FileSystemError(format!("Cannot copy file: {}", e)))?;
-            return Ok(());
+    /// Target of a template symlink that will be at `dest_rel` in the
+    /// project, refusing targets that would lead out of it
+    fn link_target(src: &Path, dest_rel: &Path) -> Result<PathBuf> {
+        let target = std::fs::read_link(src)
+            .map_err(|e| Error::FileSystemError(format!("Cannot read link: {}", e)))?;
+        let parent = dest_rel.parent().unwrap_or(Path::new(""));
+        if !stays_inside(parent, &target) {
+            return Err(Error::RenderError(format!(
+                "The symlink '{}' points to '{}', outside the project",
+                dest_rel.display(),
+                target.display()
+            )));
         }
+        Ok(target)
+    }
 
-        // Read file content
-        let content = std::fs::read_to_string(src)
+    /// Render a single file in memory
+    ///
+    /// Binary files and `raw` ones are only measured; they are copied byte
+    /// for byte when written.
+    fn render_file(
+        src: &Path,
+        parameters: &ParameterValues,
+        raw: bool,
+    ) -> Result<RenderedContent> {
+        let bytes = std::fs::read(src)
             .map_err(|e| Error::RenderError(format!("Cannot read file: {}", e)))?;
+        let size = bytes.len() as u64;
+        if raw {
+            return Ok(RenderedContent::Raw { size });
+        }
+        let content = match String::from_utf8(bytes) {
+            Ok(content) if !Self::is_binary(content.as_bytes()) => content,
+            _ => return Ok(RenderedContent::Binary { size }),
+        };
 
         // Render with Liquid
-        let rendered = Self::render_content(&content, parameters)?;
+        Self::render_content(&content, parameters).map(RenderedContent::Templated)
+    }
 
-        // Write rendered content
-        std::fs::write(dest, rendered)
-            .map_err(|e| Error::FileSystemError(format!("Cannot write file: {}", e)))?;
+    /// Write a planned file into the output directory, keeping the
+    /// permissions of its source
+    fn write_file(template_path: &Path, output_path: &Path, file: &PlannedFile) -> Result<()> {
+        let src = template_path.join(&file.source);
+        let dest = output_path.join(&file.path);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| Error::FileSystemError(format!("Cannot create dir: {}", e)))?;
+        }
 
-        Ok(())
+        match &file.content {
+            RenderedContent::Templated(text) => {
+                std::fs::write(&dest, text)
+                    .map_err(|e| Error::FileSystemError(format!("Cannot write file: {}", e)))?;
+                Self::copy_permissions(&src, &dest)
+            }
+            RenderedContent::Binary { .. } | RenderedContent::Raw { .. } => {
+                std::fs::copy(&src, &dest)
+                    .map_err(|e| Error::FileSystemError(format!("Cannot copy file: {}", e)))?;
+                Ok(())
+            }
+            RenderedContent::Link(target) => symlink(target, &dest),
+        }
+    }
+
+    /// Give `dest` the permissions of `src`, so scripts stay executable
+    pub(crate) fn copy_permissions(src: &Path, dest: &Path) -> Result<()> {
+        let permissions = std::fs::metadata(src)
+            .map_err(|e| Error::FileSystemError(format!("Cannot read file: {}", e)))?
+            .permissions();
+        std::fs::set_permissions(dest, permissions)
+            .map_err(|e| Error::FileSystemError(format!("Cannot set permissions: {}", e)))
     }
 
     /// Render content string with Liquid
-    fn render_content(content: &str, parameters: &HashMap<String, String>) -> Result<String> {
+    fn render_content(content: &str, parameters: &ParameterValues) -> Result<String> {
         // Parse Liquid template
         let template = liquid::ParserBuilder::with_stdlib()
             .build()
@@ -84,10 +378,11 @@ impl Renderer {
         // Prepare globals map for rendering
         let mut globals = liquid::Object::new();
         for (key, value) in parameters {
-            globals.insert(
-                key.clone().into(),
-                Value::scalar(value.clone()),
-            );
+            let value = match value {
+                ParameterValue::Boolean(b) => Value::scalar(*b),
+                ParameterValue::String(s) => Value::scalar(s.clone()),
+            };
+            globals.insert(key.clone().into(), value);
         }
 
         // Render
@@ -96,25 +391,99 @@ impl Renderer {
             .map_err(|e| Error::RenderError(format!("Failed to render template: {}", e)))
     }
 
-    /// Check if a file is binary
-    fn is_binary_file(path: &Path) -> bool {
-        let binary_extensions = ["png", "jpg", "jpeg", "gif", "ico", "bin", "zip", "tar", "gz"];
-
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| binary_extensions.contains(&ext.to_lowercase().as_str()))
-            .unwrap_or(false)
+    /// Whether file content is binary: it has a NUL byte near the start (as
+    /// git decides) or is not valid UTF-8
+    pub(crate) fn is_binary(content: &[u8]) -> bool {
+        content.iter().take(BINARY_SNIFF_BYTES).any(|b| *b == 0)
+            || std::str::from_utf8(content).is_err()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::template::{FileRules, TemplateMetadata};
+    use std::collections::HashMap;
+
+    fn schema_with_files(files: Option<FileRules>) -> TemplateSchema {
+        TemplateSchema {
+            template: TemplateMetadata {
+                name: "test".to_string(),
+                description: "test description".to_string(),
+                version: "1.0.0".to_string(),
+                authors: vec!["test".to_string()],
+                repository: "https://github.com/test/test".to_string(),
+                tags: vec![],
+                min_rust_version: None,
+                min_x402_cli_version: None,
+            },
+            parameters: None,
+            files,
+        }
+    }
+
+    #[test]
+    fn test_render_applies_file_rules() {
+        let template = tempfile::TempDir::new().unwrap();
+        let output = tempfile::TempDir::new().unwrap();
+        let root = template.path();
+
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "// {{ project_name }}").unwrap();
+        std::fs::write(root.join("notes.txt"), "notes").unwrap();
+        std::fs::write(root.join("target/debug/app"), "bin").unwrap();
+        std::fs::write(root.join(".git/HEAD"), "ref").unwrap();
+        std::fs::write(root.join("x402.toml"), "").unwrap();
+
+        let schema = schema_with_files(Some(FileRules {
+            include: vec!["src/**/*".to_string(), "target/**".to_string()],
+            exclude: vec!["target/**".to_string()],
+            raw: Vec::new(),
+        }));
+        let mut params = HashMap::new();
+        params.insert("project_name".to_string(), "my-app".into());
+
+        let report = Renderer::render(root, output.path(), &params, &schema).unwrap();
+
+        assert_eq!(report.rendered, vec![PathBuf::from("src/main.rs")]);
+        assert_eq!(
+            std::fs::read_to_string(output.path().join("src/main.rs")).unwrap(),
+            "// my-app"
+        );
+        assert!(report
+            .skipped
+            .contains(&(PathBuf::from("notes.txt"), SkipReason::NotIncluded)));
+        assert!(report.skipped.contains(&(
+            PathBuf::from("target"),
+            SkipReason::Excluded("target/**".to_string())
+        )));
+        assert!(!output.path().join("target").exists());
+        assert!(!output.path().join(".git").exists());
+        assert!(!output.path().join("x402.toml").exists());
+    }
+
+    #[test]
+    fn test_render_without_file_rules_uses_default_excludes() {
+        let template = tempfile::TempDir::new().unwrap();
+        let output = tempfile::TempDir::new().unwrap();
+        std::fs::write(template.path().join("README.md"), "readme").unwrap();
+        std::fs::write(template.path().join("build.log"), "log").unwrap();
+
+        let schema = schema_with_files(None);
+        let report =
+            Renderer::render(template.path(), output.path(), &HashMap::new(), &schema).unwrap();
+
+        assert_eq!(report.rendered, vec![PathBuf::from("README.md")]);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(!output.path().join("build.log").exists());
+    }
 
     #[test]
     fn test_render_simple_template() {
         let mut params = HashMap::new();
-        params.insert("project_name".to_string(), "my-app".to_string());
+        params.insert("project_name".to_string(), "my-app".into());
 
         let content = "Project: {{ project_name }}";
         let result = Renderer::render_content(content, &params).unwrap();
@@ -125,7 +494,7 @@ mod tests {
     #[test]
     fn test_render_conditional_template() {
         let mut params = HashMap::new();
-        params.insert("enable_docker".to_string(), "true".to_string());
+        params.insert("enable_docker".to_string(), true.into());
 
         let content = "{% if enable_docker %}Docker enabled{% endif %}";
         let result = Renderer::render_content(content, &params).unwrap();
@@ -134,10 +503,191 @@ mod tests {
     }
 
     #[test]
-    fn test_is_binary_file() {
-        assert!(Renderer::is_binary_file(Path::new("image.png")));
-        assert!(Renderer::is_binary_file(Path::new("archive.zip")));
-        assert!(!Renderer::is_binary_file(Path::new("main.rs")));
-        assert!(!Renderer::is_binary_file(Path::new("Cargo.toml")));
+    fn test_render_false_boolean_is_falsy() {
+        let mut params = HashMap::new();
+        params.insert("enable_docker".to_string(), false.into());
+        params.insert("db_type".to_string(), "sqlite".into());
+
+        let content = "{% if enable_docker %}Docker{% else %}No Docker{% endif %} \
+                       {% if db_type == \"sqlite\" %}SQLite{% endif %}";
+        let result = Renderer::render_content(content, &params).unwrap();
+
+        assert_eq!(result, "No Docker SQLite");
+    }
+
+    #[test]
+    fn test_render_templated_paths() {
+        let template = tempfile::TempDir::new().unwrap();
+        let output = tempfile::TempDir::new().unwrap();
+        let root = template.path();
+
+        std::fs::create_dir_all(root.join("src/{{ project_name }}")).unwrap();
+        std::fs::create_dir_all(root.join("{% if enable_docker %}docker{% endif %}")).unwrap();
+        std::fs::write(root.join("src/{{ project_name }}/lib.rs"), "// {{ project_name }}").unwrap();
+        std::fs::write(root.join("{{ \".\" }}gitignore"), "target/").unwrap();
+        std::fs::write(
+            root.join("{% if enable_docker %}docker{% endif %}/Dockerfile"),
+            "FROM rust",
+        )
+        .unwrap();
+
+        let schema = schema_with_files(None);
+        let mut params = HashMap::new();
+        params.insert("project_name".to_string(), "my-app".into());
+        params.insert("enable_docker".to_string(), false.into());
+
+        let report = Renderer::render(root, output.path(), &params, &schema).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(output.path().join("src/my-app/lib.rs")).unwrap(),
+            "// my-app"
+        );
+        assert!(output.path().join(".gitignore").exists());
+        assert!(!output.path().join("docker").exists());
+        assert_eq!(report.rendered.len(), 2);
+        assert!(report.skipped.contains(&(
+            PathBuf::from("{% if enable_docker %}docker{% endif %}"),
+            SkipReason::EmptyName
+        )));
+
+        params.insert("enable_docker".to_string(), true.into());
+        let output = tempfile::TempDir::new().unwrap();
+        Renderer::render(root, output.path(), &params, &schema).unwrap();
+        assert!(output.path().join("docker/Dockerfile").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_render_keeps_permissions_and_links() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let template = tempfile::TempDir::new().unwrap();
+        let output = tempfile::TempDir::new().unwrap();
+        let root = template.path();
+        std::fs::create_dir_all(root.join("scripts")).unwrap();
+        std::fs::write(root.join("scripts/dev.sh"), "#!/bin/sh\necho {{ project_name }}\n")
+            .unwrap();
+        std::fs::set_permissions(
+            root.join("scripts/dev.sh"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("scripts/dev.sh", root.join("dev")).unwrap();
+
+        let schema = schema_with_files(None);
+        let mut params = HashMap::new();
+        params.insert("project_name".to_string(), "my-app".into());
+        Renderer::render(root, output.path(), &params, &schema).unwrap();
+
+        let script = output.path().join("scripts/dev.sh");
+        assert_eq!(std::fs::read_to_string(&script).unwrap(), "#!/bin/sh\necho my-app\n");
+        let mode = std::fs::metadata(&script).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(
+            std::fs::read_link(output.path().join("dev")).unwrap(),
+            Path::new("scripts/dev.sh")
+        );
+
+        std::os::unix::fs::symlink("../../etc/passwd", root.join("scripts/passwd")).unwrap();
+        let output = tempfile::TempDir::new().unwrap();
+        let err = Renderer::render(root, output.path(), &params, &schema).unwrap_err();
+        assert!(err.to_string().contains("outside the project"));
+    }
+
+    #[test]
+    fn test_render_name_rejects_escaping_paths() {
+        let mut params = HashMap::new();
+        let rel = Path::new("{{ name }}");
+        let render = |value: &str, params: &mut ParameterValues| {
+            params.insert("name".to_string(), value.into());
+            Renderer::render_name(OsStr::new("{{ name }}"), rel, params)
+        };
+
+        assert_eq!(render("api/v1", &mut params).unwrap(), Some(PathBuf::from("api/v1")));
+        assert_eq!(render("", &mut params).unwrap(), None);
+        assert!(render("..", &mut params).is_err());
+        assert!(render("a/../../etc", &mut params).is_err());
+        assert!(render("/etc/passwd", &mut params).is_err());
+        assert!(render("  ", &mut params).is_err());
+        assert!(render("a//b", &mut params).is_err());
+        assert!(render(".", &mut params).is_err());
+        assert!(render("a\\b", &mut params).is_err());
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(Renderer::is_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(Renderer::is_binary(b"wOF2\x00\x01"));
+        assert!(Renderer::is_binary(&[0xff, 0xfe, b'a']));
+        assert!(!Renderer::is_binary("fn main() {} // ünïcode ✓".as_bytes()));
+        assert!(!Renderer::is_binary(b""));
+    }
+
+    #[test]
+    fn test_render_copies_binary_and_raw_files() {
+        let template = tempfile::TempDir::new().unwrap();
+        let output = tempfile::TempDir::new().unwrap();
+        let root = template.path();
+        std::fs::create_dir_all(root.join(".github/workflows")).unwrap();
+        let font = b"wOF2\0\x01{{ project_name }}\0";
+        std::fs::write(root.join("font.woff2"), font).unwrap();
+        let workflow = "run: echo ${{ github.sha }}\n";
+        std::fs::write(root.join(".github/workflows/ci.yml"), workflow).unwrap();
+        std::fs::write(root.join("README.md"), "# {{ project_name }}").unwrap();
+
+        let schema = schema_with_files(Some(FileRules {
+            raw: vec![".github/workflows/**".to_string()],
+            ..FileRules::default()
+        }));
+        let mut params = HashMap::new();
+        params.insert("project_name".to_string(), "my-app".into());
+        let report = Renderer::render(root, output.path(), &params, &schema).unwrap();
+
+        assert_eq!(std::fs::read(output.path().join("font.woff2")).unwrap(), font);
+        assert_eq!(
+            std::fs::read_to_string(output.path().join(".github/workflows/ci.yml")).unwrap(),
+            workflow
+        );
+        assert_eq!(
+            std::fs::read_to_string(output.path().join("README.md")).unwrap(),
+            "# my-app"
+        );
+        assert_eq!(
+            report.copied,
+            vec![PathBuf::from(".github/workflows/ci.yml"), PathBuf::from("font.woff2")]
+        );
+        assert_eq!(report.rendered.len(), 3);
+    }
+
+    #[test]
+    fn test_preview_renders_in_memory() {
+        let template = tempfile::TempDir::new().unwrap();
+        let root = template.path();
+        std::fs::create_dir_all(root.join("{{ project_name }}")).unwrap();
+        std::fs::write(root.join("{{ project_name }}/lib.rs"), "// {{ project_name }}").unwrap();
+        std::fs::write(root.join("logo.png"), b"\x89PNG\0\0").unwrap();
+        std::fs::write(root.join("notes.txt"), "notes").unwrap();
+
+        let filter = FileFilter::new(Some(&FileRules {
+            exclude: vec!["*.txt".to_string()],
+            ..FileRules::default()
+        }))
+        .unwrap();
+        let mut params = HashMap::new();
+        params.insert("project_name".to_string(), "my-app".into());
+        let preview = Renderer::preview(root, &params, &filter).unwrap();
+
+        let lib = &preview.files[1];
+        assert_eq!(lib.path, PathBuf::from("my-app/lib.rs"));
+        assert_eq!(lib.source, PathBuf::from("{{ project_name }}/lib.rs"));
+        assert!(matches!(&lib.content, RenderedContent::Templated(text) if text == "// my-app"));
+        assert_eq!(lib.size(), 9);
+        assert_eq!(preview.files[0].path, PathBuf::from("logo.png"));
+        assert!(matches!(preview.files[0].content, RenderedContent::Binary { size: 6 }));
+        assert_eq!(
+            preview.skipped,
+            vec![(PathBuf::from("notes.txt"), SkipReason::Excluded("*.txt".to_string()))]
+        );
+        assert_eq!(std::fs::read_dir(root).unwrap().count(), 3);
     }
 }
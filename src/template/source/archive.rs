@@ -0,0 +1,84 @@
+//! Templates downloaded as `.zip` or `.tar.gz` archives from any URL
+
+use super::{Materialized, TemplateSource};
+use crate::error::{Error, Result};
+use crate::template::downloader::{ArchiveFormat, Downloader};
+use crate::template::Provenance;
+use async_trait::async_trait;
+use std::path::Path;
+
+/// A template archive served over HTTP(S), e.g. from a self-hosted mirror
+pub struct ArchiveSource {
+    url: String,
+}
+
+impl ArchiveSource {
+    /// Create a source for an archive URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+#[async_trait]
+impl TemplateSource for ArchiveSource {
+    async fn materialize(&self, scratch: &Path) -> Result<Materialized> {
+        let format = ArchiveFormat::from_url(&self.url).ok_or_else(|| Error::ValidationError {
+            field: "template".to_string(),
+            message: format!("'{}' is not a .zip, .tar.gz or .tgz URL", self.url),
+        })?;
+
+        Downloader::new()
+            .download_archive(&self.url, format, scratch)
+            .await?;
+
+        Ok(Materialized {
+            path: scratch.to_path_buf(),
+            provenance: Provenance::new(self.url.clone(), None, None),
+            working_copy: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_archive_source_extracts_tarball() {
+        let mut server = mockito::Server::new_async().await;
+        let body = tar_gz(&[
+            ("api-1.0/x402.toml", "[template]"),
+            ("api-1.0/src/main.rs", "fn main() {}"),
+        ]);
+        let mock = server
+            .mock("GET", "/templates/api-1.0.tar.gz")
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let url = format!("{}/templates/api-1.0.tar.gz", server.url());
+        let scratch = tempfile::TempDir::new().unwrap();
+        let materialized = ArchiveSource::new(url.clone())
+            .materialize(scratch.path())
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert!(materialized.path.join("x402.toml").is_file());
+        assert!(materialized.path.join("src/main.rs").is_file());
+        assert_eq!(materialized.provenance.source, url);
+    }
+}
@@ -0,0 +1,153 @@
+//! Templates cloned with the system `git`
+
+use super::{Materialized, TemplateSource};
+use crate::error::{Error, Result};
+use crate::template::Provenance;
+use async_trait::async_trait;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Any repository the system `git` can clone (self-hosted servers, SSH
+/// remotes, mirrors)
+pub struct GitSource {
+    url: String,
+    reference: Option<String>,
+}
+
+impl GitSource {
+    /// Create a source for a clone URL, optionally pinned to a branch, tag
+    /// or commit
+    pub fn new(url: impl Into<String>, reference: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            reference,
+        }
+    }
+}
+
+#[async_trait]
+impl TemplateSource for GitSource {
+    async fn materialize(&self, scratch: &Path) -> Result<Materialized> {
+        let checkout = scratch.join("checkout");
+        let checkout_str = checkout.to_string_lossy().into_owned();
+
+        // `git checkout` has no `--` for revisions, so refuse anything that
+        // would be read as an option
+        if let Some(reference) = self.reference.as_deref().filter(|r| r.starts_with('-')) {
+            return Err(Error::ParameterError(format!(
+                "Invalid git reference '{}'",
+                reference
+            )));
+        }
+
+        let mut shallow = vec!["clone", "--quiet", "--depth", "1"];
+        if let Some(reference) = &self.reference {
+            shallow.extend(["--branch", reference.as_str()]);
+        }
+        shallow.extend(["--", self.url.as_str(), checkout_str.as_str()]);
+
+        if let Err(e) = run_git(&shallow).await {
+            // --branch only accepts branches and tags; commits need a full clone
+            let Some(reference) = &self.reference else {
+                return Err(e);
+            };
+            if checkout.exists() {
+                std::fs::remove_dir_all(&checkout)?;
+            }
+            run_git(&["clone", "--quiet", "--", &self.url, &checkout_str]).await?;
+            run_git(&["-C", &checkout_str, "checkout", "--quiet", reference]).await?;
+        }
+
+        let commit = run_git(&["-C", &checkout_str, "rev-parse", "HEAD"]).await?;
+
+        Ok(Materialized {
+            path: checkout,
+            provenance: Provenance::new(self.url.clone(), self.reference.clone(), Some(commit)),
+            working_copy: false,
+        })
+    }
+}
+
+/// Run git and return its trimmed stdout
+async fn run_git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .await
+        .map_err(|e| Error::FileSystemError(format!("Failed to run git: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(Error::NetworkError(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[tokio::test]
+    async fn test_git_source_clones_tag_and_commit() {
+        let repo = tempfile::TempDir::new().unwrap();
+        git(repo.path(), &["init", "--quiet"]);
+        std::fs::write(repo.path().join("x402.toml"), "v1").unwrap();
+        git(repo.path(), &["add", "."]);
+        git(repo.path(), &["commit", "--quiet", "-m", "v1"]);
+        git(repo.path(), &["tag", "v1"]);
+        std::fs::write(repo.path().join("x402.toml"), "v2").unwrap();
+        git(repo.path(), &["commit", "--quiet", "-am", "v2"]);
+
+        let url = format!("file://{}", repo.path().display());
+
+        let scratch = tempfile::TempDir::new().unwrap();
+        let tagged = GitSource::new(url.clone(), Some("v1".to_string()))
+            .materialize(scratch.path())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(tagged.path.join("x402.toml")).unwrap(), "v1");
+        let v1_commit = tagged.provenance.commit.unwrap();
+        assert_eq!(v1_commit.len(), 40);
+
+        let scratch = tempfile::TempDir::new().unwrap();
+        let pinned = GitSource::new(url, Some(v1_commit.clone()))
+            .materialize(scratch.path())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(pinned.path.join("x402.toml")).unwrap(), "v1");
+        assert_eq!(pinned.provenance.commit, Some(v1_commit));
+    }
+
+    #[tokio::test]
+    async fn test_git_source_does_not_pass_options() {
+        let scratch = tempfile::TempDir::new().unwrap();
+        let marker = scratch.path().join("pwned");
+
+        let url = format!("--upload-pack=touch {}", marker.display());
+        let result = GitSource::new(url, None).materialize(scratch.path()).await;
+        assert!(result.is_err());
+        assert!(!marker.exists());
+
+        let result = GitSource::new("https://example.com/t.git", Some("--orphan=x".to_string()))
+            .materialize(scratch.path())
+            .await;
+        assert!(matches!(result, Err(Error::ParameterError(_))));
+    }
+}
@@ -0,0 +1,170 @@
+//! Templates downloaded as GitHub zipballs
+
+use super::{Materialized, TemplateSource};
+use crate::discovery::github::is_commit_sha;
+use crate::discovery::{GitHubDiscovery, GitHubHost, TemplateInfo};
+use crate::error::{Error, Result};
+use crate::template::downloader::ArchiveFormat;
+use crate::template::store::{ArchiveStore, RepoKey};
+use crate::template::{Downloader, Provenance};
+use async_trait::async_trait;
+use std::path::Path;
+
+/// A GitHub repository, fetched at a commit resolved from a branch, tag or
+/// SHA (the repository's default branch when none is given)
+///
+/// Zipballs are kept in the [`ArchiveStore`], so a commit is downloaded at
+/// most once and can be used again offline. A source that does not persist
+/// still reads the store but never writes to it.
+pub struct GitHubSource {
+    host: GitHubHost,
+    template: TemplateInfo,
+    reference: Option<String>,
+    store: Option<ArchiveStore>,
+    offline: bool,
+    persist: bool,
+}
+
+impl GitHubSource {
+    /// Create a source for a template discovered on `host`
+    pub fn new(host: GitHubHost, template: TemplateInfo, reference: Option<String>) -> Self {
+        Self {
+            host,
+            template,
+            reference,
+            store: ArchiveStore::new().ok(),
+            offline: false,
+            persist: true,
+        }
+    }
+
+    /// Keep archives in a different store
+    #[allow(dead_code)] // Intentionally public for library users
+    pub fn with_store(mut self, store: ArchiveStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Only use archives already in the store, never the network
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Save downloaded archives and resolved references to the store
+    /// (the default); turned off for dry runs
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    fn key(&self) -> RepoKey {
+        RepoKey {
+            host: self.host.name.clone(),
+            owner: self.template.owner.clone(),
+            repo: self.template.repo.clone(),
+        }
+    }
+
+    /// Look up the repository's default branch, asking GitHub when the
+    /// template came from a listing that did not include it
+    async fn default_branch(&self, discovery: &GitHubDiscovery) -> Result<String> {
+        if let Some(branch) = &self.template.default_branch {
+            return Ok(branch.clone());
+        }
+
+        let info = discovery
+            .get_template(&self.template.owner, &self.template.repo)
+            .await?;
+        Ok(info.default_branch.unwrap_or_else(|| "main".to_string()))
+    }
+
+    /// Find the reference and commit we used last time we were online
+    fn resolve_offline(&self, store: &ArchiveStore) -> Result<(String, String)> {
+        let key = self.key();
+        let unavailable = |what: String| {
+            Error::NetworkError(format!(
+                "{} of {}/{} is not in the local cache; run once without --offline to fetch it",
+                what, key.owner, key.repo
+            ))
+        };
+
+        let reference = match &self.reference {
+            Some(reference) => reference.clone(),
+            None => store
+                .default_branch(&key)
+                .ok_or_else(|| unavailable("The default branch".to_string()))?,
+        };
+
+        let commit = match store.lookup_ref(&key, &reference) {
+            Some(commit) => commit,
+            None if is_commit_sha(&reference) => reference.clone(),
+            None => return Err(unavailable(format!("'{}'", reference))),
+        };
+        if store.get(&key, &commit).is_none() {
+            return Err(unavailable(format!("'{}'", reference)));
+        }
+
+        Ok((reference, commit))
+    }
+}
+
+#[async_trait]
+impl TemplateSource for GitHubSource {
+    async fn materialize(&self, scratch: &Path) -> Result<Materialized> {
+        let key = self.key();
+
+        let (reference, commit) = match (&self.store, self.offline) {
+            (Some(store), true) => self.resolve_offline(store)?,
+            (None, true) => {
+                return Err(Error::CacheError(
+                    "Cannot determine cache directory for offline use".to_string(),
+                ))
+            }
+            (_, false) => {
+                let discovery = GitHubDiscovery::for_host(self.host.clone());
+                let reference = match &self.reference {
+                    Some(reference) => reference.clone(),
+                    None => self.default_branch(&discovery).await?,
+                };
+                let commit = discovery
+                    .resolve_commit(&key.owner, &key.repo, &reference)
+                    .await?;
+                (reference, commit)
+            }
+        };
+
+        let downloader = Downloader::for_host(self.host.clone());
+        let cached = self.store.as_ref().and_then(|store| store.get(&key, &commit));
+        match (&self.store, cached) {
+            (_, Some(archive)) => downloader.extract(&archive, ArchiveFormat::Zip, scratch)?,
+            (Some(store), None) if self.persist => {
+                let (downloader, key, commit) = (&downloader, &key, &commit);
+                let archive = store
+                    .put(key, commit, |path| async move {
+                        downloader
+                            .download_zipball(&key.owner, &key.repo, commit, &path)
+                            .await
+                    })
+                    .await?;
+                downloader.extract(&archive, ArchiveFormat::Zip, scratch)?;
+            }
+            _ => {
+                downloader
+                    .download(&key.owner, &key.repo, &commit, scratch)
+                    .await?
+            }
+        }
+        if let Some(store) = self.store.as_ref().filter(|_| self.persist && !self.offline) {
+            // Best effort: only needed to find this commit offline later
+            let is_default = self.reference.is_none();
+            let _ = store.record_ref(&key, &reference, &commit, is_default);
+        }
+
+        Ok(Materialized {
+            path: scratch.to_path_buf(),
+            provenance: Provenance::new(self.template.url.clone(), Some(reference), Some(commit)),
+            working_copy: false,
+        })
+    }
+}
@@ -0,0 +1,64 @@
+//! Templates read directly from a local directory
+
+use super::{Materialized, TemplateSource};
+use crate::error::{Error, Result};
+use crate::template::Provenance;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+/// A template directory on the local filesystem, used in place
+pub struct LocalSource {
+    path: PathBuf,
+}
+
+impl LocalSource {
+    /// Create a source for a template directory
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl TemplateSource for LocalSource {
+    async fn materialize(&self, _scratch: &Path) -> Result<Materialized> {
+        if !self.path.is_dir() {
+            return Err(Error::TemplateNotFound(self.path.display().to_string()));
+        }
+
+        Ok(Materialized {
+            path: self.path.clone(),
+            provenance: Provenance::new(self.path.display().to_string(), None, None),
+            working_copy: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_source_uses_directory_in_place() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let scratch = tempfile::TempDir::new().unwrap();
+
+        let materialized = LocalSource::new(dir.path())
+            .materialize(scratch.path())
+            .await
+            .unwrap();
+
+        assert_eq!(materialized.path, dir.path());
+        assert!(materialized.working_copy);
+        assert!(materialized.provenance.commit.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_local_source_missing_directory() {
+        let scratch = tempfile::TempDir::new().unwrap();
+        let result = LocalSource::new("/nonexistent/x402-template")
+            .materialize(scratch.path())
+            .await;
+
+        assert!(matches!(result, Err(Error::TemplateNotFound(_))));
+    }
+}
@@ -0,0 +1,373 @@
+//! Pluggable template sources.
+//!
+//! A [`TemplateSource`] makes a template available on disk and reports where
+//! it came from. The create command picks an implementation from the syntax
+//! of the template reference (see [`SourceRef::parse`]):
+//!
+//! | Reference                                   | Source              |
+//! |---------------------------------------------|---------------------|
+//! | `./dir`, `/abs/dir`, `file:///dir`          | [`LocalSource`]     |
+//! | `https://host/t.zip`, `https://host/t.tgz`  | [`ArchiveSource`]   |
+//! | `owner/repo[@ref]`, `https://github.com/..` | [`GitHubSource`]    |
+//! | URLs on a configured GitHub Enterprise host | [`GitHubSource`]    |
+//! | `git+https://..`, `ssh://..`, `*.git`       | [`GitSource`]       |
+//! | anything else                               | looked up by name   |
+
+pub mod archive;
+pub mod git;
+pub mod github;
+pub mod local;
+
+pub use archive::ArchiveSource;
+pub use git::GitSource;
+pub use github::GitHubSource;
+pub use local::LocalSource;
+
+use super::downloader::ArchiveFormat;
+use super::Provenance;
+use crate::config::Config;
+use crate::discovery::GitHubHost;
+use crate::error::{Error, Result};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+/// A template made available on disk by a [`TemplateSource`]
+#[derive(Debug, Clone)]
+pub struct Materialized {
+    /// Directory containing x402.toml
+    pub path: PathBuf,
+
+    /// Where the template came from
+    pub provenance: Provenance,
+
+    /// Whether `path` is a working copy whose `.gitignore` and `target/`
+    /// must be respected when rendering
+    pub working_copy: bool,
+}
+
+/// Somewhere a template can be fetched from
+#[async_trait]
+pub trait TemplateSource: Send + Sync {
+    /// Make the template available on disk, using the empty `scratch`
+    /// directory for any files that have to be fetched
+    async fn materialize(&self, scratch: &Path) -> Result<Materialized>;
+}
+
+/// A parsed template reference, before anything is fetched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceRef {
+    /// Template directory on the local filesystem
+    Local(PathBuf),
+
+    /// `.zip` or `.tar.gz` archive at an HTTP(S) URL
+    Archive(String),
+
+    /// GitHub repository, optionally pinned with `@ref`
+    GitHub {
+        /// GitHub or GitHub Enterprise host name
+        host: String,
+        /// Repository owner
+        owner: String,
+        /// Repository name
+        repo: String,
+        /// Branch, tag or commit SHA
+        reference: Option<String>,
+    },
+
+    /// Any repository reachable with the system `git`
+    Git {
+        /// Clone URL
+        url: String,
+        /// Branch, tag or commit SHA
+        reference: Option<String>,
+    },
+
+    /// Template name to look up in the discovered template list
+    Name(String),
+}
+
+impl SourceRef {
+    /// Work out which kind of source a template reference points to
+    ///
+    /// URLs on github.com or any host in `config` are GitHub repositories;
+    /// `owner/repo` shorthands refer to the configured default host.
+    pub fn parse(template_ref: &str, config: &Config) -> Result<Self> {
+        if let Some(path) = Self::local_path(template_ref) {
+            return Ok(SourceRef::Local(path));
+        }
+
+        let is_http = template_ref.starts_with("https://") || template_ref.starts_with("http://");
+        if is_http && ArchiveFormat::from_url(template_ref).is_some() {
+            return Ok(SourceRef::Archive(template_ref.to_string()));
+        }
+
+        if let Some(url) = template_ref.strip_prefix("git+") {
+            let (url, reference) = split_ref(url)?;
+            return Ok(SourceRef::Git {
+                url: url.to_string(),
+                reference,
+            });
+        }
+
+        let (location, reference) = split_ref(template_ref)?;
+
+        for host in GitHubHost::known_hosts(config) {
+            let github = GitHubHost::from_config(&host, config);
+            if !location.starts_with(&format!("{}/", github.web_url)) {
+                continue;
+            }
+            let (owner, repo) = github.parse_repo_url(location).ok_or_else(|| {
+                Error::ValidationError {
+                    field: "template".to_string(),
+                    message: format!("Invalid {} repository URL", host),
+                }
+            })?;
+            return Ok(SourceRef::GitHub {
+                host,
+                owner,
+                repo,
+                reference,
+            });
+        }
+
+        let is_git = is_http
+            || location.starts_with("ssh://")
+            || location.starts_with("git://")
+            || location.starts_with("git@")
+            || location.ends_with(".git");
+        if is_git {
+            return Ok(SourceRef::Git {
+                url: location.to_string(),
+                reference,
+            });
+        }
+
+        if location.contains('/') {
+            let parts: Vec<&str> = location.split('/').collect();
+            if parts.len() != 2 || parts.iter().any(|p| p.is_empty()) {
+                return Err(Error::ValidationError {
+                    field: "template".to_string(),
+                    message: "Template reference must be in format 'owner/repo[@ref]'"
+                        .to_string(),
+                });
+            }
+            return Ok(SourceRef::GitHub {
+                host: config.default_host().to_string(),
+                owner: parts[0].to_string(),
+                repo: parts[1].to_string(),
+                reference,
+            });
+        }
+
+        if reference.is_some() {
+            return Err(Error::ValidationError {
+                field: "template".to_string(),
+                message: "Use 'owner/repo@ref' to pin a template by name".to_string(),
+            });
+        }
+
+        Ok(SourceRef::Name(location.to_string()))
+    }
+
+    /// Interpret a template reference as a local path
+    ///
+    /// `file://` URLs, absolute paths and paths starting with `.` are always
+    /// local; anything else is local only if it is an existing directory
+    /// that contains an x402.toml.
+    fn local_path(template_ref: &str) -> Option<PathBuf> {
+        let path = if let Some(rest) = template_ref.strip_prefix("file://") {
+            PathBuf::from(rest.strip_prefix("localhost").unwrap_or(rest))
+        } else {
+            let path = PathBuf::from(template_ref);
+            let explicit = path.is_absolute() || template_ref.starts_with('.');
+            if !explicit && !path.join("x402.toml").is_file() {
+                return None;
+            }
+            path
+        };
+
+        Some(path.canonicalize().unwrap_or(path))
+    }
+}
+
+/// Split a trailing `@ref` off a reference
+///
+/// The first `@` in the repository path counts, so references may contain
+/// slashes (`owner/repo@feature/login`). The user part of a URL
+/// (`ssh://git@host/...`) or scp-style remote (`git@host:owner/repo.git`)
+/// is skipped.
+fn split_ref(template_ref: &str) -> Result<(&str, Option<String>)> {
+    let path_start = if let Some(scheme_end) = template_ref.find("://") {
+        let authority = scheme_end + "://".len();
+        template_ref[authority..]
+            .find('/')
+            .map_or(template_ref.len(), |slash| authority + slash)
+    } else {
+        match template_ref.find(':') {
+            Some(colon) if !template_ref[..colon].contains('/') => colon + 1,
+            _ => 0,
+        }
+    };
+
+    match template_ref[path_start..].find('@') {
+        Some(at) => {
+            let at = path_start + at;
+            let reference = &template_ref[at + 1..];
+            if reference.is_empty() {
+                return Err(Error::ValidationError {
+                    field: "template".to_string(),
+                    message: "Expected a branch, tag or commit after '@'".to_string(),
+                });
+            }
+            Ok((&template_ref[..at], Some(reference.to_string())))
+        }
+        None => Ok((template_ref, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(template_ref: &str) -> Result<SourceRef> {
+        SourceRef::parse(template_ref, &Config::default())
+    }
+
+    fn github(owner: &str, repo: &str, reference: Option<&str>) -> SourceRef {
+        SourceRef::GitHub {
+            host: "github.com".to_string(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            reference: reference.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_github_references() {
+        assert_eq!(parse("user/repo").unwrap(), github("user", "repo", None));
+        assert_eq!(
+            parse("user/repo@v1.2.0").unwrap(),
+            github("user", "repo", Some("v1.2.0"))
+        );
+        assert_eq!(
+            parse("https://github.com/user/repo@main").unwrap(),
+            github("user", "repo", Some("main"))
+        );
+        assert_eq!(
+            parse("https://github.com/user/repo.git").unwrap(),
+            github("user", "repo", None)
+        );
+        assert!(parse("user/repo@").is_err());
+        assert!(parse("a/b/c").is_err());
+    }
+
+    #[test]
+    fn test_parse_references_with_slashes() {
+        assert_eq!(
+            parse("user/repo@feature/login").unwrap(),
+            github("user", "repo", Some("feature/login"))
+        );
+        assert_eq!(
+            parse("https://github.com/user/repo@release/1.x").unwrap(),
+            github("user", "repo", Some("release/1.x"))
+        );
+        assert_eq!(
+            parse("git@git.example.com:team/template.git@feature/login").unwrap(),
+            SourceRef::Git {
+                url: "git@git.example.com:team/template.git".to_string(),
+                reference: Some("feature/login".to_string()),
+            }
+        );
+        assert_eq!(
+            parse("ssh://git@git.example.com/team/template.git").unwrap(),
+            SourceRef::Git {
+                url: "ssh://git@git.example.com/team/template.git".to_string(),
+                reference: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_git_references() {
+        assert_eq!(
+            parse("git@git.example.com:team/template.git@v2").unwrap(),
+            SourceRef::Git {
+                url: "git@git.example.com:team/template.git".to_string(),
+                reference: Some("v2".to_string()),
+            }
+        );
+        assert_eq!(
+            parse("git+https://git.example.com/team/template").unwrap(),
+            SourceRef::Git {
+                url: "https://git.example.com/team/template".to_string(),
+                reference: None,
+            }
+        );
+        assert_eq!(
+            parse("https://gitlab.example.com/team/template").unwrap(),
+            SourceRef::Git {
+                url: "https://gitlab.example.com/team/template".to_string(),
+                reference: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_archive_local_and_name() {
+        assert_eq!(
+            parse("https://mirror.example.com/templates/api-1.0.tar.gz").unwrap(),
+            SourceRef::Archive("https://mirror.example.com/templates/api-1.0.tar.gz".to_string())
+        );
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let canonical = dir.path().canonicalize().unwrap();
+        let url = format!("file://{}", dir.path().display());
+        assert_eq!(parse(&url).unwrap(), SourceRef::Local(canonical.clone()));
+        assert_eq!(
+            parse(&dir.path().display().to_string()).unwrap(),
+            SourceRef::Local(canonical)
+        );
+        assert_eq!(
+            parse("./does-not-exist").unwrap(),
+            SourceRef::Local(PathBuf::from("./does-not-exist"))
+        );
+
+        assert_eq!(
+            parse("basic-api").unwrap(),
+            SourceRef::Name("basic-api".to_string())
+        );
+        assert!(parse("basic-api@v1").is_err());
+    }
+
+    #[test]
+    fn test_parse_enterprise_references() {
+        let mut config = Config {
+            default_host: Some("github.example.com".to_string()),
+            ..Config::default()
+        };
+        config
+            .hosts
+            .insert("github.example.com".to_string(), Default::default());
+
+        let enterprise = |owner: &str, repo: &str, reference: Option<&str>| SourceRef::GitHub {
+            host: "github.example.com".to_string(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            reference: reference.map(str::to_string),
+        };
+
+        assert_eq!(
+            SourceRef::parse("payments/api-template@v3", &config).unwrap(),
+            enterprise("payments", "api-template", Some("v3"))
+        );
+        assert_eq!(
+            SourceRef::parse("https://github.example.com/payments/api-template", &config)
+                .unwrap(),
+            enterprise("payments", "api-template", None)
+        );
+        assert_eq!(
+            SourceRef::parse("https://github.com/user/repo", &config).unwrap(),
+            github("user", "repo", None)
+        );
+    }
+}
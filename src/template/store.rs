@@ -0,0 +1,341 @@
+//! Content-addressed store of downloaded template archives
+//!
+//! Zipballs are kept under the cache directory, keyed by the commit they
+//! were downloaded at:
+//!
+//! ```text
+//! ~/.cache/x402/archives/{host}/{owner}/{repo}/{commit}.zip
+//! ~/.cache/x402/archives/{host}/{owner}/{repo}/refs.json
+//! ```
+//!
+//! A commit never changes, so a stored archive can be re-used without asking
+//! GitHub. `refs.json` remembers which commit each branch or tag resolved to
+//! the last time we were online, so `--offline` can still find it.
+
+use crate::discovery::cache::{write_atomic, CACHE_DIR_NAME};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const ARCHIVES_DIR_NAME: &str = "archives";
+const REFS_FILE_NAME: &str = "refs.json";
+
+/// Identifies a repository in the store
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoKey {
+    /// GitHub host name
+    pub host: String,
+    /// Repository owner
+    pub owner: String,
+    /// Repository name
+    pub repo: String,
+}
+
+/// Last known commits of a repository's branches and tags
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct RefIndex {
+    /// The repository's default branch, when it was last looked up
+    #[serde(default)]
+    default_branch: Option<String>,
+
+    /// Commit each branch or tag resolved to
+    #[serde(default)]
+    refs: BTreeMap<String, String>,
+}
+
+/// An archive found in the store
+#[derive(Debug, Clone)]
+pub struct StoredArchive {
+    /// Repository the archive belongs to
+    pub key: RepoKey,
+    /// Commit the archive was downloaded at
+    pub commit: String,
+    /// Location of the archive
+    pub path: PathBuf,
+    /// Size in bytes
+    pub size: u64,
+    /// When the archive was downloaded
+    pub modified: Option<SystemTime>,
+    /// Whether a recorded branch or tag still points at this commit
+    pub referenced: bool,
+}
+
+/// Downloaded template archives, keyed by owner/repo/commit
+#[derive(Debug, Clone)]
+pub struct ArchiveStore {
+    root: PathBuf,
+}
+
+impl ArchiveStore {
+    /// Open the store in the user's cache directory
+    pub fn new() -> Result<Self> {
+        let root = dirs::cache_dir()
+            .ok_or_else(|| Error::CacheError("Cannot determine cache directory".to_string()))?
+            .join(CACHE_DIR_NAME)
+            .join(ARCHIVES_DIR_NAME);
+        Ok(Self::with_root(root))
+    }
+
+    /// Open a store rooted at a custom directory
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Directory holding the store
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Directory holding everything stored for a repository
+    fn repo_dir(&self, key: &RepoKey) -> PathBuf {
+        self.root.join(&key.host).join(&key.owner).join(&key.repo)
+    }
+
+    /// Where the archive for a commit lives (whether or not it exists)
+    pub fn archive_path(&self, key: &RepoKey, commit: &str) -> PathBuf {
+        self.repo_dir(key).join(format!("{}.zip", commit))
+    }
+
+    /// The stored archive for a commit, if we have it
+    pub fn get(&self, key: &RepoKey, commit: &str) -> Option<PathBuf> {
+        let path = self.archive_path(key, commit);
+        path.is_file().then_some(path)
+    }
+
+    /// Add an archive for a commit, writing it with `write` into a temporary
+    /// file that is moved into place only once complete
+    pub async fn put<F, Fut>(&self, key: &RepoKey, commit: &str, write: F) -> Result<PathBuf>
+    where
+        F: FnOnce(PathBuf) -> Fut,
+        Fut: std::future::Future<Output = Result<()>>,
+    {
+        let dir = self.repo_dir(key);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| Error::CacheError(format!("Cannot create {}: {}", dir.display(), e)))?;
+
+        let temp = tempfile::NamedTempFile::new_in(&dir)
+            .map_err(|e| Error::CacheError(format!("Cannot create temp file: {}", e)))?;
+        write(temp.path().to_path_buf()).await?;
+
+        let path = self.archive_path(key, commit);
+        temp.persist(&path)
+            .map_err(|e| Error::CacheError(format!("Cannot store archive: {}", e)))?;
+        Ok(path)
+    }
+
+    /// Remember the commit a branch or tag resolved to
+    pub fn record_ref(
+        &self,
+        key: &RepoKey,
+        reference: &str,
+        commit: &str,
+        is_default_branch: bool,
+    ) -> Result<()> {
+        let mut index = self.read_index(key);
+        index.refs.insert(reference.to_string(), commit.to_string());
+        if is_default_branch {
+            index.default_branch = Some(reference.to_string());
+        }
+
+        self.write_index(key, &index)
+    }
+
+    /// The commit a branch or tag resolved to when we were last online
+    pub fn lookup_ref(&self, key: &RepoKey, reference: &str) -> Option<String> {
+        self.read_index(key).refs.get(reference).cloned()
+    }
+
+    /// The default branch recorded for a repository
+    pub fn default_branch(&self, key: &RepoKey) -> Option<String> {
+        self.read_index(key).default_branch
+    }
+
+    /// Every archive in the store, sorted by repository and age
+    pub fn archives(&self) -> Result<Vec<StoredArchive>> {
+        let mut archives = Vec::new();
+        if !self.root.is_dir() {
+            return Ok(archives);
+        }
+
+        // {root}/{host}/{owner}/{repo}/{commit}.zip
+        for entry in walkdir::WalkDir::new(&self.root)
+            .min_depth(4)
+            .max_depth(4)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            let commit = match path.file_name().and_then(|n| n.to_str()) {
+                Some(name) if !name.starts_with('.') => match name.strip_suffix(".zip") {
+                    Some(commit) => commit.to_string(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let Some(key) = self.key_for(path) else {
+                continue;
+            };
+
+            let metadata = entry
+                .metadata()
+                .map_err(|e| Error::CacheError(format!("Cannot read {}: {}", path.display(), e)))?;
+            let referenced = self.read_index(&key).refs.values().any(|c| *c == commit);
+            archives.push(StoredArchive {
+                key,
+                commit,
+                path: path.to_path_buf(),
+                size: metadata.len(),
+                modified: metadata.modified().ok(),
+                referenced,
+            });
+        }
+
+        archives.sort_by(|a, b| {
+            (&a.key.host, &a.key.owner, &a.key.repo, b.modified)
+                .cmp(&(&b.key.host, &b.key.owner, &b.key.repo, a.modified))
+        });
+        Ok(archives)
+    }
+
+    /// Delete an archive, forgetting any refs that pointed at it
+    pub fn remove(&self, archive: &StoredArchive) -> Result<()> {
+        std::fs::remove_file(&archive.path)
+            .map_err(|e| Error::CacheError(format!("Cannot delete archive: {}", e)))?;
+
+        let mut index = self.read_index(&archive.key);
+        let before = index.refs.len();
+        index.refs.retain(|_, commit| *commit != archive.commit);
+        if index.refs.len() != before {
+            self.write_index(&archive.key, &index)?;
+        }
+        Ok(())
+    }
+
+    /// Delete the whole store
+    pub fn clear(&self) -> Result<()> {
+        match std::fs::remove_dir_all(&self.root) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(Error::CacheError(format!("Cannot delete archives: {}", e))),
+        }
+    }
+
+    /// The repository an archive path belongs to
+    fn key_for(&self, archive: &Path) -> Option<RepoKey> {
+        let relative = archive.strip_prefix(&self.root).ok()?;
+        let mut parts = relative.iter().map(|part| part.to_str().map(str::to_string));
+        Some(RepoKey {
+            host: parts.next()??,
+            owner: parts.next()??,
+            repo: parts.next()??,
+        })
+    }
+
+    fn read_index(&self, key: &RepoKey) -> RefIndex {
+        // A missing or unreadable index just means "nothing known"
+        std::fs::read_to_string(self.repo_dir(key).join(REFS_FILE_NAME))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_index(&self, key: &RepoKey, index: &RefIndex) -> Result<()> {
+        let content = serde_json::to_string_pretty(index)
+            .map_err(|e| Error::CacheError(format!("Cannot serialize refs: {}", e)))?;
+        write_atomic(&self.repo_dir(key).join(REFS_FILE_NAME), content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> RepoKey {
+        RepoKey {
+            host: "github.com".to_string(),
+            owner: "x402".to_string(),
+            repo: "basic-api".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_and_get() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = ArchiveStore::with_root(dir.path());
+        let commit = "0123456789abcdef0123456789abcdef01234567";
+
+        assert!(store.get(&key(), commit).is_none());
+
+        let path = store
+            .put(&key(), commit, |path| async move {
+                std::fs::write(path, b"zip").map_err(Error::from)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(store.get(&key(), commit), Some(path.clone()));
+        assert!(path.ends_with(format!("github.com/x402/basic-api/{}.zip", commit)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_put_leaves_nothing_behind() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = ArchiveStore::with_root(dir.path());
+
+        let result = store
+            .put(&key(), "abc", |_| async { Err(Error::NetworkError("offline".to_string())) })
+            .await;
+
+        assert!(result.is_err());
+        assert!(store.get(&key(), "abc").is_none());
+    }
+
+    #[test]
+    fn test_record_and_lookup_refs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = ArchiveStore::with_root(dir.path());
+
+        store.record_ref(&key(), "main", "aaa", true).unwrap();
+        store.record_ref(&key(), "v1.0.0", "bbb", false).unwrap();
+        store.record_ref(&key(), "main", "ccc", true).unwrap();
+
+        assert_eq!(store.lookup_ref(&key(), "main").as_deref(), Some("ccc"));
+        assert_eq!(store.lookup_ref(&key(), "v1.0.0").as_deref(), Some("bbb"));
+        assert_eq!(store.lookup_ref(&key(), "v2"), None);
+        assert_eq!(store.default_branch(&key()).as_deref(), Some("main"));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_remove_archives() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = ArchiveStore::with_root(dir.path().join("archives"));
+        assert!(store.archives().unwrap().is_empty());
+
+        for commit in ["aaa", "bbb"] {
+            store
+                .put(&key(), commit, |path| async move {
+                    std::fs::write(path, b"zip").map_err(Error::from)
+                })
+                .await
+                .unwrap();
+        }
+        store.record_ref(&key(), "main", "bbb", true).unwrap();
+
+        let archives = store.archives().unwrap();
+        assert_eq!(archives.len(), 2);
+        assert!(archives.iter().all(|a| a.key == key() && a.size == 3));
+        let stale = archives.iter().find(|a| a.commit == "aaa").unwrap();
+        assert!(!stale.referenced);
+
+        let current = archives.iter().find(|a| a.commit == "bbb").unwrap();
+        assert!(current.referenced);
+        store.remove(current).unwrap();
+        assert_eq!(store.lookup_ref(&key(), "main"), None);
+        assert_eq!(store.archives().unwrap().len(), 1);
+
+        store.clear().unwrap();
+        assert!(store.archives().unwrap().is_empty());
+    }
+}
@@ -7,7 +7,7 @@ use tempfile::TempDir;
 #[test]
 fn test_project_name_validation_empty() {
     // Project names cannot be empty
-    let name = "";
+    let name = String::new();
     assert!(name.is_empty(), "Empty name should fail validation");
 }
 
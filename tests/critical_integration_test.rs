@@ -311,7 +311,7 @@ fn test_workflow_parameter_validation_chain() {
     std::fs::create_dir(&path).unwrap();
 
     // Scenario: User provides valid string parameter
-    let project_name = "my-app";
+    let project_name = String::from("my-app");
     let is_valid = !project_name.is_empty()
         && project_name
             .chars()
@@ -319,7 +319,7 @@ fn test_workflow_parameter_validation_chain() {
     assert!(is_valid);
 
     // Create project with this name
-    let project_path = path.join(project_name);
+    let project_path = path.join(&project_name);
     std::fs::create_dir(&project_path).unwrap();
     assert!(project_path.exists());
 }
@@ -337,13 +337,11 @@ fn test_error_recovery_on_partial_creation() {
 
     // Simulate failure - cleanup all files
     let mut count = 0;
-    for entry in std::fs::read_dir(&project_path).unwrap() {
-        if let Ok(entry) = entry {
-            let path = entry.path();
-            if path.is_file() {
-                std::fs::remove_file(&path).unwrap();
-                count += 1;
-            }
+    for entry in std::fs::read_dir(&project_path).unwrap().flatten() {
+        let path = entry.path();
+        if path.is_file() {
+            std::fs::remove_file(&path).unwrap();
+            count += 1;
         }
     }
 
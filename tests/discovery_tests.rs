@@ -1,4 +1,4 @@
-/// Template discovery and caching tests
+//! Template discovery and caching tests
 
 use std::fs;
 use tempfile::TempDir;
@@ -87,7 +87,7 @@ fn test_template_sorting_by_stars() {
         stars: u32,
     }
 
-    let mut templates = vec![
+    let mut templates = [
         Template {
             name: "Template A".to_string(),
             stars: 10,
@@ -102,7 +102,7 @@ fn test_template_sorting_by_stars() {
         },
     ];
 
-    templates.sort_by(|a, b| b.stars.cmp(&a.stars));
+    templates.sort_by_key(|t| std::cmp::Reverse(t.stars));
 
     assert_eq!(templates[0].name, "Template B");
     assert_eq!(templates[1].name, "Template C");
@@ -116,7 +116,7 @@ fn test_template_filtering_by_tags() {
         tags: Vec<String>,
     }
 
-    let templates = vec![
+    let templates = [
         Template {
             tags: vec!["axum".to_string(), "api".to_string()],
         },
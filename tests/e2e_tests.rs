@@ -0,0 +1,742 @@
+//! End-to-end tests against a local stand-in for the GitHub API
+//!
+//! `X402_GITHUB_API_URL` points the binary at a mockito server that serves
+//! the search, repository, commit and zipball endpoints, so the whole
+//! pipeline (discovery, download, extraction, rendering) runs offline.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+const SHA: &str = "0123456789abcdef0123456789abcdef01234567";
+
+const REPO_JSON: &str = r#"{
+    "name": "basic-api",
+    "description": "Basic x402 API",
+    "html_url": "https://github.com/x402/basic-api",
+    "owner": { "login": "x402" },
+    "stargazers_count": 42,
+    "language": "Rust",
+    "topics": ["x402-template"],
+    "default_branch": "main"
+}"#;
+
+const MANIFEST: &str = r#"
+[template]
+name = "basic-api"
+description = "Basic x402 API"
+version = "1.0.0"
+authors = ["x402"]
+repository = "https://github.com/x402/basic-api"
+
+[parameters]
+enable_docker = { type = "boolean", default = true }
+"#;
+
+/// A zipball laid out the way GitHub serves them: everything under a
+/// single `{owner}-{repo}-{sha}/` directory
+fn zipball() -> Vec<u8> {
+    let files = [
+        ("x402.toml", MANIFEST),
+        ("Cargo.toml", "[package]\nname = \"{{ project_name }}\"\n"),
+        (
+            "README.md",
+            "{% if enable_docker %}docker{% else %}no docker{% endif %}\n",
+        ),
+    ];
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (path, content) in files {
+        writer
+            .start_file(
+                format!("x402-basic-api-0123456/{}", path),
+                zip::write::FileOptions::default(),
+            )
+            .unwrap();
+        writer.write_all(content.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+/// Run `create` with the API, config, cache and token isolated
+fn run_create(server: &mockito::Server, home: &Path, args: &[&str]) -> Output {
+    let mut full_args = vec!["create"];
+    full_args.extend_from_slice(args);
+    run(server, home, &full_args)
+}
+
+/// Run the binary with the API, config, cache and token isolated
+fn run(server: &mockito::Server, home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cargo-x402"))
+        .current_dir(home)
+        .env("X402_GITHUB_API_URL", server.url())
+        .env("X402_CONFIG", home.join("config.toml"))
+        .env("HOME", home)
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("GITHUB_TOKEN", "test-token")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "command failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_create_by_name_from_search_results() {
+    let mut server = mockito::Server::new();
+    let search = server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::Any)
+        .with_header("content-type", "application/json")
+        .with_body(format!(r#"{{ "total_count": 1, "items": [{}] }}"#, REPO_JSON))
+        .create();
+    let commit = server
+        .mock("GET", "/repos/x402/basic-api/commits/main")
+        .with_body(SHA)
+        .create();
+    let download = server
+        .mock("GET", format!("/repos/x402/basic-api/zipball/{}", SHA).as_str())
+        .match_header("authorization", "Bearer test-token")
+        .with_body(zipball())
+        .create();
+
+    let home = TempDir::new().unwrap();
+    let output = run_create(
+        &server,
+        home.path(),
+        &["--template", "basic-api", "--name", "my-api", "--defaults"],
+    );
+    assert_success(&output);
+
+    search.assert();
+    commit.assert();
+    download.assert();
+
+    let project = home.path().join("my-api");
+    assert_eq!(
+        fs::read_to_string(project.join("Cargo.toml")).unwrap(),
+        "[package]\nname = \"my-api\"\n"
+    );
+    assert_eq!(fs::read_to_string(project.join("README.md")).unwrap(), "docker\n");
+    assert!(!project.join("x402.toml").exists());
+
+    let provenance = fs::read_to_string(project.join(".x402-template.toml")).unwrap();
+    assert!(provenance.contains("ref = \"main\""));
+    assert!(provenance.contains(SHA));
+}
+
+#[test]
+fn test_create_pinned_shorthand() {
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/repos/x402/basic-api")
+        .with_body(REPO_JSON)
+        .create();
+    server
+        .mock("GET", "/repos/x402/basic-api/commits/v1.0.0")
+        .with_body(SHA)
+        .create();
+    let download = server
+        .mock("GET", format!("/repos/x402/basic-api/zipball/{}", SHA).as_str())
+        .with_body(zipball())
+        .create();
+
+    let home = TempDir::new().unwrap();
+    let output = run_create(
+        &server,
+        home.path(),
+        &[
+            "--template",
+            "x402/basic-api@v1.0.0",
+            "--name",
+            "pinned",
+            "--defaults",
+            "--define",
+            "enable_docker=false",
+        ],
+    );
+    assert_success(&output);
+    download.assert();
+
+    let project = home.path().join("pinned");
+    assert_eq!(
+        fs::read_to_string(project.join("README.md")).unwrap(),
+        "no docker\n"
+    );
+    let provenance = fs::read_to_string(project.join(".x402-template.toml")).unwrap();
+    assert!(provenance.contains("ref = \"v1.0.0\""));
+}
+
+#[test]
+fn test_create_unknown_ref_fails_cleanly() {
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/repos/x402/basic-api")
+        .with_body(REPO_JSON)
+        .create();
+    server
+        .mock("GET", "/repos/x402/basic-api/commits/v9")
+        .with_status(422)
+        .create();
+
+    let home = TempDir::new().unwrap();
+    let output = run_create(
+        &server,
+        home.path(),
+        &["--template", "x402/basic-api", "--ref", "v9", "--name", "broken", "--defaults"],
+    );
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("v9"));
+    assert!(!home.path().join("broken").exists());
+}
+
+/// Write a discovery cache entry where `dirs::cache_dir` looks on Linux
+/// (`XDG_CACHE_HOME`) and macOS (`~/Library/Caches`)
+fn write_cache(home: &Path, last_updated: &str) {
+    let template = r#"{
+        "name": "Basic x402 API",
+        "description": "Basic x402 API",
+        "url": "https://github.com/x402/basic-api",
+        "owner": "x402",
+        "repo": "basic-api",
+        "stars": 42,
+        "language": "Rust"
+    }"#;
+    let entry = format!(
+        r#"{{ "version": 1, "last_updated": "{}", "templates": [{}] }}"#,
+        last_updated, template
+    );
+    for dir in [home.join("cache/x402"), home.join("Library/Caches/x402")] {
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("templates.json"), &entry).unwrap();
+    }
+}
+
+#[test]
+fn test_list_falls_back_to_stale_cache_when_rate_limited() {
+    let mut server = mockito::Server::new();
+    let search = server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::Any)
+        .with_status(403)
+        .with_header("x-ratelimit-remaining", "0")
+        .with_header("x-ratelimit-reset", "4102444800")
+        .with_body(r#"{"message": "API rate limit exceeded"}"#)
+        .create();
+
+    let home = TempDir::new().unwrap();
+    write_cache(home.path(), "2020-01-01T00:00:00Z");
+
+    let output = run(&server, home.path(), &["list"]);
+    assert_success(&output);
+    search.assert();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("basic-api"));
+    assert!(format!("{}{}", stdout, stderr).contains("rate limit"));
+}
+
+#[test]
+fn test_list_rate_limited_without_cache_fails() {
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::Any)
+        .with_status(429)
+        .with_header("retry-after", "3600")
+        .create();
+
+    let home = TempDir::new().unwrap();
+    let output = run(&server, home.path(), &["list"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("rate limit exceeded"));
+}
+
+#[test]
+fn test_create_offline_after_online_use() {
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::Any)
+        .with_body(format!(r#"{{ "total_count": 1, "items": [{}] }}"#, REPO_JSON))
+        .create();
+    server
+        .mock("GET", "/repos/x402/basic-api/commits/main")
+        .with_body(SHA)
+        .create();
+    server
+        .mock("GET", format!("/repos/x402/basic-api/zipball/{}", SHA).as_str())
+        .with_body(zipball())
+        .create();
+
+    let home = TempDir::new().unwrap();
+    let args = ["--template", "basic-api", "--name", "first", "--defaults"];
+    assert_success(&run_create(&server, home.path(), &args));
+
+    // Nothing answers now: any request would fail the run
+    let unreachable = mockito::Server::new();
+    let args = ["--offline", "--template", "basic-api", "--name", "second", "--defaults"];
+    let output = run_create(&unreachable, home.path(), &args);
+    assert_success(&output);
+
+    let provenance = fs::read_to_string(home.path().join("second/.x402-template.toml")).unwrap();
+    assert!(provenance.contains(SHA));
+}
+
+#[test]
+fn test_offline_list_serves_stale_cache() {
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+    write_cache(home.path(), "2020-01-01T00:00:00Z");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-x402"))
+        .env("X402_OFFLINE", "1")
+        .env("X402_GITHUB_API_URL", server.url())
+        .env("X402_CONFIG", home.path().join("config.toml"))
+        .env("HOME", home.path())
+        .env("XDG_CACHE_HOME", home.path().join("cache"))
+        .args(["list"])
+        .output()
+        .unwrap();
+    assert_success(&output);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("basic-api"));
+    assert!(format!("{}{}", stdout, stderr).contains("out of date"));
+}
+
+#[test]
+fn test_create_offline_without_cached_archive_fails() {
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+    let output = run_create(
+        &server,
+        home.path(),
+        &["--offline", "--template", "x402/basic-api", "--name", "nope", "--defaults"],
+    );
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("without --offline"));
+    assert!(!home.path().join("nope").exists());
+}
+
+#[test]
+fn test_cache_ttl_flag_keeps_old_list_fresh() {
+    // No mocks: a refresh would fail the run
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+    write_cache(home.path(), "2020-01-01T00:00:00Z");
+
+    let output = run(&server, home.path(), &["list", "--cache-ttl", "1000000"]);
+    assert_success(&output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("basic-api"));
+}
+
+#[test]
+fn test_cache_info_prune_and_clear() {
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/repos/x402/basic-api")
+        .with_body(REPO_JSON)
+        .create();
+    server
+        .mock("GET", "/repos/x402/basic-api/commits/main")
+        .with_body(SHA)
+        .create();
+    server
+        .mock("GET", format!("/repos/x402/basic-api/zipball/{}", SHA).as_str())
+        .with_body(zipball())
+        .create();
+
+    let home = TempDir::new().unwrap();
+    write_cache(home.path(), "2020-01-01T00:00:00Z");
+    let args = ["--template", "x402/basic-api", "--name", "cached", "--defaults"];
+    assert_success(&run_create(&server, home.path(), &args));
+
+    let output = run(&server, home.path(), &["cache", "info"]);
+    assert_success(&output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 templates"));
+    assert!(stdout.contains("stale"));
+    assert!(stdout.contains("1 archives"));
+    assert!(stdout.contains("x402/basic-api"));
+
+    // The archive is still what `main` points at
+    let output = run(&server, home.path(), &["cache", "prune"]);
+    assert_success(&output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Nothing to prune"));
+
+    let output = run(&server, home.path(), &["cache", "clear"]);
+    assert_success(&output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 archives"));
+
+    let output = run(&server, home.path(), &["cache", "info"]);
+    assert_success(&output);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("1 templates"));
+}
+
+#[test]
+fn test_list_recovers_from_corrupt_cache() {
+    let mut server = mockito::Server::new();
+    let search = server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::Any)
+        .with_body(format!(r#"{{ "total_count": 1, "items": [{}] }}"#, REPO_JSON))
+        .create();
+
+    let home = TempDir::new().unwrap();
+    for dir in [home.path().join("cache/x402"), home.path().join("Library/Caches/x402")] {
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("templates.json"), r#"{ "last_updated": "2025-"#).unwrap();
+    }
+
+    let output = run(&server, home.path(), &["list"]);
+    assert_success(&output);
+    search.assert();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("basic-api"));
+
+    // The broken file was replaced, so the next run is served from the cache
+    let offline = run(&server, home.path(), &["--offline", "list"]);
+    assert_success(&offline);
+    assert!(String::from_utf8_lossy(&offline.stdout).contains("basic-api"));
+}
+
+#[test]
+fn test_info_reads_manifest_through_contents_api() {
+    let mut server = mockito::Server::new();
+    let contents = server
+        .mock("GET", "/repos/x402/basic-api/contents/x402.toml")
+        .match_query(mockito::Matcher::UrlEncoded("ref".into(), "v1.0.0".into()))
+        .with_body(MANIFEST)
+        .expect(2)
+        .create();
+
+    let home = TempDir::new().unwrap();
+    let output = run(&server, home.path(), &["info", "x402/basic-api@v1.0.0"]);
+    assert_success(&output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("enable_docker"));
+    assert!(stdout.contains("boolean"));
+    assert!(stdout.contains("target/**, .git/**, *.log"), "{}", stdout);
+    assert!(stdout.contains("Manifest is valid"));
+
+    let args = ["info", "x402/basic-api", "--ref", "v1.0.0", "--json"];
+    let output = run(&server, home.path(), &args);
+    assert_success(&output);
+    contents.assert();
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["valid"], true);
+    assert_eq!(json["reference"], "v1.0.0");
+    assert_eq!(json["template"]["name"], "basic-api");
+    assert_eq!(json["parameters"]["enable_docker"]["type"], "boolean");
+    assert_eq!(json["parameters"]["enable_docker"]["default"], true);
+}
+
+#[test]
+fn test_info_reports_invalid_local_manifest() {
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+    let template = home.path().join("template");
+    fs::create_dir(&template).unwrap();
+    fs::write(
+        template.join("x402.toml"),
+        MANIFEST.replace("version = \"1.0.0\"", "version = \"one\""),
+    )
+    .unwrap();
+
+    let output = run(&server, home.path(), &["info", "./template", "--json"]);
+    assert!(!output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["valid"], false);
+    assert!(json["error"].as_str().unwrap().contains("template.version"));
+}
+
+#[test]
+fn test_validate_gates_on_errors() {
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+    let template = home.path().join("template");
+    fs::create_dir(&template).unwrap();
+    fs::write(template.join("x402.toml"), MANIFEST).unwrap();
+    fs::write(
+        template.join("README.md"),
+        "{{ project_name }}\n{% if enable_docker %}docker{% endif %}\n",
+    )
+    .unwrap();
+
+    let output = run(&server, home.path(), &["validate", "template"]);
+    assert_success(&output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Template is valid"));
+
+    fs::write(template.join("Cargo.toml"), "name = \"{{ project_nmae }}\"\n").unwrap();
+    let output = run(&server, home.path(), &["lint", "template"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Cargo.toml:1: 'project_nmae'"));
+}
+
+#[test]
+fn test_test_command_reports_failing_combinations() {
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+    let template = home.path().join("template");
+    fs::create_dir(&template).unwrap();
+    fs::write(template.join("x402.toml"), MANIFEST).unwrap();
+    fs::write(template.join("flags.txt"), "docker={{ enable_docker }}\n").unwrap();
+
+    let output = run(
+        &server,
+        home.path(),
+        &["test", "template", "--check", "grep -q docker=true flags.txt"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success());
+    assert!(stdout.contains("Testing 2 combinations"), "{}", stdout);
+    assert!(stdout.contains("1 passed, 1 failed"), "{}", stdout);
+    assert!(
+        stdout.contains("--defaults --name template -D enable_docker=false"),
+        "{}",
+        stdout
+    );
+
+    // Capped to the all-defaults combination, which passes
+    let output = run(
+        &server,
+        home.path(),
+        &[
+            "test",
+            "template",
+            "--check",
+            "grep -q docker=true flags.txt",
+            "--max-combinations",
+            "1",
+        ],
+    );
+    assert_success(&output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Testing 1 of 2 combinations"));
+
+    // Checking nothing must not count as a pass
+    for flag in ["--sample", "--max-combinations"] {
+        let output = run(&server, home.path(), &["test", "template", flag, "0"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains(flag));
+    }
+}
+
+#[test]
+fn test_template_init_writes_a_valid_template() {
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+
+    let output = run(&server, home.path(), &["template", "init", "my-template", "--defaults"]);
+    assert_success(&output);
+    let manifest = fs::read_to_string(home.path().join("my-template/x402.toml")).unwrap();
+    assert!(manifest.contains("[parameters]"));
+    assert!(home.path().join("my-template/src/main.rs").exists());
+    let readme = fs::read_to_string(home.path().join("my-template/README.md")).unwrap();
+    assert!(readme.contains("## Using this template"));
+
+    // The sample files use every parameter, so even strict linting passes
+    assert_success(&run(&server, home.path(), &["validate", "--strict", "my-template"]));
+    assert_success(&run(
+        &server,
+        home.path(),
+        &["test", "my-template", "--check", "test -f Cargo.toml"],
+    ));
+
+    let output = run(&server, home.path(), &["template", "init", "my-template", "--defaults"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+}
+
+#[test]
+fn test_template_extract_round_trips_a_project() {
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+    let project = home.path().join("pay-svc");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::create_dir_all(project.join("target/debug")).unwrap();
+    fs::create_dir_all(project.join(".git")).unwrap();
+    fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"pay-svc\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    fs::write(
+        project.join("src/main.rs"),
+        "// {{ kept literally }}\nfn main() { println!(\"pay-svc on localhost:4021\"); }\n",
+    )
+    .unwrap();
+    fs::write(project.join("target/debug/pay-svc"), "binary").unwrap();
+    fs::write(project.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+
+    let output = run(
+        &server,
+        home.path(),
+        &["template", "extract", "pay-svc", "--defaults", "--var", "greeting=on"],
+    );
+    assert_success(&output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("reproduces the project"), "{}", stdout);
+
+    let template = home.path().join("pay-svc-template");
+    assert!(!template.join("target").exists());
+    assert!(!template.join(".git").exists());
+    let manifest = fs::read_to_string(template.join("x402.toml")).unwrap();
+    assert!(manifest.contains("port = { type = \"string\", default = \"4021\""), "{}", manifest);
+    assert!(manifest.contains("greeting = "), "{}", manifest);
+    let main = fs::read_to_string(template.join("src/main.rs")).unwrap();
+    assert!(main.contains("{{ project_name }} {{ greeting }} localhost:{{ port }}"), "{}", main);
+
+    // Creating from the template with the original name gives the original file
+    let output = run_create(
+        &server,
+        home.path(),
+        &["--template", "./pay-svc-template", "--name", "copy", "--defaults"],
+    );
+    assert_success(&output);
+    let created = fs::read_to_string(home.path().join("copy/src/main.rs")).unwrap();
+    assert!(created.starts_with("// {{ kept literally }}\n"), "{}", created);
+
+    let output = run(&server, home.path(), &["template", "extract", "pay-svc", "--defaults"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+}
+
+#[test]
+fn test_create_dry_run_writes_nothing() {
+    let server = mockito::Server::new();
+    let home = TempDir::new().unwrap();
+    let template = home.path().join("template");
+    fs::create_dir_all(template.join("src")).unwrap();
+    fs::write(template.join("x402.toml"), MANIFEST).unwrap();
+    fs::write(template.join("Cargo.toml"), "[package]\nname = \"{{ project_name }}\"\n").unwrap();
+    fs::write(template.join("src/main.rs"), "fn main() {}\n").unwrap();
+    fs::write(template.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 0]).unwrap();
+
+    let output = run_create(
+        &server,
+        home.path(),
+        &[
+            "--template",
+            "./template",
+            "--name",
+            "preview",
+            "--defaults",
+            "--dry-run",
+            "--diff",
+            "Cargo.toml",
+        ],
+    );
+    assert_success(&output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("├── Cargo.toml"), "{}", stdout);
+    assert!(stdout.contains("└── main.rs"), "{}", stdout);
+    assert!(stdout.contains("binary"), "{}", stdout);
+    assert!(stdout.contains("3 files"), "{}", stdout);
+    assert!(stdout.contains("-name = \"{{ project_name }}\""), "{}", stdout);
+    assert!(stdout.contains("+name = \"preview\""), "{}", stdout);
+    assert!(!home.path().join("preview").exists());
+
+    let output = run_create(&server, home.path(), &["--diff", "*", "--name", "x"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--dry-run"));
+}
+
+/// Every file under the cache directories with its contents
+fn cache_snapshot(home: &Path) -> Vec<(std::path::PathBuf, Vec<u8>)> {
+    let mut files = Vec::new();
+    for dir in [home.join("cache"), home.join("Library/Caches")] {
+        // Only one of the two exists, depending on the platform
+        for entry in walkdir::WalkDir::new(dir).sort_by_file_name().into_iter().flatten() {
+            if entry.file_type().is_file() {
+                files.push((entry.path().to_path_buf(), fs::read(entry.path()).unwrap()));
+            }
+        }
+    }
+    files
+}
+
+#[test]
+fn test_create_dry_run_leaves_cache_untouched() {
+    const PINNED: &str = "89abcdef0123456789abcdef0123456789abcdef";
+
+    let mut server = mockito::Server::new();
+    server
+        .mock("GET", "/repos/x402/basic-api")
+        .with_body(REPO_JSON)
+        .create();
+    server
+        .mock("GET", "/repos/x402/basic-api/commits/main")
+        .with_body(SHA)
+        .create();
+    server
+        .mock("GET", "/repos/x402/basic-api/commits/v1.0.0")
+        .with_body(PINNED)
+        .create();
+    for sha in [SHA, PINNED] {
+        server
+            .mock("GET", format!("/repos/x402/basic-api/zipball/{}", sha).as_str())
+            .with_body(zipball())
+            .create();
+    }
+
+    let home = TempDir::new().unwrap();
+    let args = ["--template", "x402/basic-api", "--name", "first", "--defaults"];
+    assert_success(&run_create(&server, home.path(), &args));
+    let before = cache_snapshot(home.path());
+    assert!(!before.is_empty());
+
+    let args = [
+        "--template",
+        "x402/basic-api@v1.0.0",
+        "--name",
+        "preview",
+        "--defaults",
+        "--dry-run",
+    ];
+    assert_success(&run_create(&server, home.path(), &args));
+    assert_eq!(cache_snapshot(home.path()), before);
+    assert!(!home.path().join("preview").exists());
+}
+
+#[test]
+fn test_create_dry_run_by_name_leaves_cache_untouched() {
+    let mut server = mockito::Server::new();
+    let search = server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::Any)
+        .with_header("content-type", "application/json")
+        .with_body(format!(r#"{{ "total_count": 1, "items": [{}] }}"#, REPO_JSON))
+        .create();
+    server
+        .mock("GET", "/repos/x402/basic-api/commits/main")
+        .with_body(SHA)
+        .create();
+    server
+        .mock("GET", format!("/repos/x402/basic-api/zipball/{}", SHA).as_str())
+        .with_body(zipball())
+        .create();
+
+    let home = TempDir::new().unwrap();
+    let args = ["--template", "basic-api", "--name", "preview", "--defaults", "--dry-run"];
+    assert_success(&run_create(&server, home.path(), &args));
+    search.assert();
+
+    assert!(cache_snapshot(home.path()).is_empty());
+    assert!(!home.path().join("preview").exists());
+}
@@ -1,6 +1,6 @@
-/// Integration tests for cargo-x402
-///
-/// Tests the full workflow of template discovery, validation, and rendering
+//! Integration tests for cargo-x402
+//!
+//! Tests the full workflow of template discovery, validation, and rendering
 
 use std::fs;
 use tempfile::TempDir;
@@ -228,7 +228,7 @@ fn matches_pattern(filename: &str, pattern: &str) -> bool {
 
     if pattern.contains("*") {
         let prefix = pattern.split('*').next().unwrap_or("");
-        let suffix = pattern.split('*').last().unwrap_or("");
+        let suffix = pattern.split('*').next_back().unwrap_or("");
 
         return filename.starts_with(prefix) && filename.ends_with(suffix);
     }
@@ -317,3 +317,61 @@ mod tests {
         assert!(url.ends_with(".zip"));
     }
 }
+
+#[test]
+fn test_create_from_local_template() {
+    let template_dir = TempDir::new().unwrap();
+    let root = template_dir.path();
+    fs::write(
+        root.join("x402.toml"),
+        r#"
+[template]
+name = "local-template"
+description = "A template read from disk"
+version = "0.1.0"
+authors = ["Test Author"]
+repository = "https://github.com/test/local-template"
+
+[parameters]
+enable_docker = { type = "boolean", default = true }
+"#,
+    )
+    .unwrap();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join("target/debug")).unwrap();
+    fs::write(root.join("src/main.rs"), "// {{ project_name }}\n").unwrap();
+    fs::write(
+        root.join("README.md"),
+        "{% if enable_docker %}docker{% else %}no docker{% endif %}\n",
+    )
+    .unwrap();
+    fs::write(root.join(".gitignore"), ".env\n").unwrap();
+    fs::write(root.join(".env"), "SECRET=1\n").unwrap();
+    fs::write(root.join("target/debug/app"), "binary").unwrap();
+
+    let work_dir = TempDir::new().unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_cargo-x402"))
+        .current_dir(work_dir.path())
+        .args(["create", "--name", "my-app", "--defaults", "--define"])
+        .arg("enable_docker=false")
+        .arg("--template")
+        .arg(format!("file://{}", root.display()))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "create failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let project = work_dir.path().join("my-app");
+    assert_eq!(
+        fs::read_to_string(project.join("src/main.rs")).unwrap(),
+        "// my-app\n"
+    );
+    assert_eq!(fs::read_to_string(project.join("README.md")).unwrap(), "no docker\n");
+    assert!(project.join(".gitignore").exists());
+    assert!(!project.join(".env").exists());
+    assert!(!project.join("target").exists());
+    assert!(!project.join("x402.toml").exists());
+}
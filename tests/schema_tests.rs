@@ -1,4 +1,4 @@
-/// Schema validation tests
+//! Schema validation tests
 
 #[test]
 fn test_valid_template_schema() {
@@ -142,7 +142,7 @@ fn test_parameter_validation_rules() {
     assert!(!re.is_match("invalid_name")); // underscore not allowed
 
     // Test enum options
-    let options = vec!["dev", "staging", "prod"];
+    let options = ["dev", "staging", "prod"];
     assert!(options.contains(&"dev"));
     assert!(options.contains(&"prod"));
     assert!(!options.contains(&"unknown"));
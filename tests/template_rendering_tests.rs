@@ -1,4 +1,4 @@
-/// Template rendering and file operation tests
+//! Template rendering and file operation tests
 
 use std::fs;
 use tempfile::TempDir;
@@ -101,9 +101,7 @@ fn test_exclude_git_directory() {
     ];
 
     for path in paths {
-        if path.starts_with(".git/") {
-            assert!(should_exclude_path(path));
-        } else if path == ".git" {
+        if path.starts_with(".git/") || path == ".git" {
             assert!(should_exclude_path(path));
         }
     }
@@ -209,12 +207,8 @@ fn test_symlink_handling() {
     // Test that symlinks are either followed or skipped correctly
     let should_follow_symlinks = false; // Default behavior: don't follow symlinks
 
-    if should_follow_symlinks {
-        // Would test symlink resolution
-    } else {
-        // Symlinks should be skipped
-        assert!(true);
-    }
+    // Symlinks should be skipped
+    assert!(!should_follow_symlinks);
 }
 
 // Helper functions
@@ -252,7 +246,7 @@ fn glob_matches(pattern: &str, path: &str) -> bool {
             let remaining = if prefix.is_empty() {
                 path
             } else {
-                &path[prefix.len()..].trim_start_matches('/')
+                path[prefix.len()..].trim_start_matches('/')
             };
 
             // Check if remaining matches suffix pattern